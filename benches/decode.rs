@@ -0,0 +1,24 @@
+use std::{hint::black_box, time::Instant};
+
+use image_parser::ppm::parse_ppm_file;
+
+const WIDTH: usize = 3840;
+const HEIGHT: usize = 2160;
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    for maxval in [255, 251, 65535] {
+        let sample_size = if maxval < 256 { 1 } else { 2 };
+        let mut file = format!("P6 {WIDTH} {HEIGHT} {maxval}\n").into_bytes();
+        file.extend((0..WIDTH * HEIGHT * 3 * sample_size).map(|i| (i % 251) as u8));
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            black_box(parse_ppm_file(black_box(&file)).unwrap());
+        }
+        println!(
+            "decode 4K maxval {maxval}: {:?} per image",
+            start.elapsed() / ITERATIONS
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{image::Rgba, Image, ImageRead};
+
+impl Image {
+    /// Counts the 4-connected regions of pixels whose red, green or blue channel
+    /// differs from `bg` by more than `tolerance`
+    pub fn connected_components(&self, bg: Rgba, tolerance: u8) -> usize {
+        let is_foreground = |rgba: Rgba| {
+            rgba.r.abs_diff(bg.r) > tolerance
+                || rgba.g.abs_diff(bg.g) > tolerance
+                || rgba.b.abs_diff(bg.b) > tolerance
+        };
+
+        let width = self.width();
+        let height = self.height();
+        let mut visited = vec![false; self.len()];
+        let mut stack = Vec::new();
+        let mut components = 0;
+
+        for start in 0..self.len() {
+            if visited[start] || !is_foreground(self[start].rgba()) {
+                continue;
+            }
+
+            components += 1;
+            visited[start] = true;
+            stack.push(start);
+            while let Some(index) = stack.pop() {
+                let x = index % width;
+                let y = index / width;
+                let neighbours = [
+                    (x > 0).then(|| index - 1),
+                    (x + 1 < width).then(|| index + 1),
+                    (y > 0).then(|| index - width),
+                    (y + 1 < height).then(|| index + width),
+                ];
+                for neighbour in neighbours.into_iter().flatten() {
+                    if !visited[neighbour] && is_foreground(self[neighbour].rgba()) {
+                        visited[neighbour] = true;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+
+        components
+    }
+
+    /// Counts of each value of the red, green, blue and alpha channels, in
+    /// this order
+    pub fn histogram(&self) -> [[u32; 256]; 4] {
+        ImageRead::histogram(self)
+    }
+
+    /// Counts of each value of the luminance, see Rgba::luminance()
+    pub fn luminance_histogram(&self) -> [u32; 256] {
+        ImageRead::luminance_histogram(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::image::Pixel;
+
+    const WHITE: Rgba = Rgba {
+        r: 255,
+        g: 255,
+        b: 255,
+        a: 255,
+    };
+
+    fn blobs(mask: &[u8]) -> Vec<Pixel> {
+        mask.iter()
+            .map(|&value| {
+                Pixel::from(if value == 1 {
+                    Rgba {
+                        r: 20,
+                        g: 20,
+                        b: 20,
+                        a: 255,
+                    }
+                } else {
+                    WHITE
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn three_blobs() {
+        #[rustfmt::skip]
+        let image = Image::new(5, 4, blobs(&[
+            1, 1, 0, 0, 1,
+            1, 0, 0, 1, 1,
+            0, 0, 0, 0, 0,
+            0, 1, 1, 1, 0,
+        ]));
+        assert_eq!(image.connected_components(WHITE, 10), 3);
+    }
+
+    #[test]
+    fn diagonal_is_not_connected() {
+        #[rustfmt::skip]
+        let image = Image::new(2, 2, blobs(&[
+            1, 0,
+            0, 1,
+        ]));
+        assert_eq!(image.connected_components(WHITE, 0), 2);
+        assert_eq!(image.connected_components(WHITE, 255), 0);
+    }
+
+    #[test]
+    fn histogram_of_solid_color() {
+        let image = Image::new(
+            3,
+            2,
+            [Pixel::from(Rgba {
+                r: 10,
+                g: 20,
+                b: 30,
+                a: 40,
+            }); 6],
+        );
+
+        let histogram = image.histogram();
+        for (channel, value) in [10, 20, 30, 40].into_iter().enumerate() {
+            assert_eq!(histogram[channel][value], 6);
+            assert_eq!(histogram[channel].iter().sum::<u32>(), 6);
+        }
+
+        let luminance = image.luminance_histogram();
+        assert_eq!(luminance[18], 6);
+        assert_eq!(luminance.iter().sum::<u32>(), 6);
+    }
+
+    #[test]
+    fn luminance_histogram_bins() {
+        let image = Image::new(3, 1, [WHITE.into(), WHITE.into(), Pixel::from(0)]);
+        let histogram = image.luminance_histogram();
+        assert_eq!((histogram[0], histogram[255]), (1, 2));
+    }
+}
@@ -0,0 +1,71 @@
+use alloc::string::String;
+use core::fmt::Write;
+
+use crate::{image::Rgba, Image};
+
+const UPPER_HALF_BLOCK: char = '\u{2580}';
+
+impl Image {
+    pub fn to_ansi(&self, cols: usize) -> String {
+        let mut output = String::new();
+        if cols == 0 || self.width() == 0 || self.height() == 0 {
+            return output;
+        }
+
+        // Each character cell holds two vertically stacked pixels, which keeps
+        // the preview's aspect ratio close to square on most terminals
+        let rows = (self.height() * cols / self.width()).max(1);
+        for y in (0..rows).step_by(2) {
+            for x in 0..cols {
+                let top = self.sample(x, y, cols, rows);
+                write!(output, "\x1b[38;2;{};{};{}m", top.r, top.g, top.b).unwrap();
+                if y + 1 < rows {
+                    let bottom = self.sample(x, y + 1, cols, rows);
+                    write!(output, "\x1b[48;2;{};{};{}m", bottom.r, bottom.g, bottom.b).unwrap();
+                } else {
+                    output.push_str("\x1b[49m");
+                }
+                output.push(UPPER_HALF_BLOCK);
+            }
+            output.push_str("\x1b[0m\n");
+        }
+
+        output
+    }
+
+    fn sample(&self, x: usize, y: usize, cols: usize, rows: usize) -> Rgba {
+        let source_x = x * self.width() / cols;
+        let source_y = y * self.height() / rows;
+        self[source_y * self.width() + source_x].rgba()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::image::Pixel;
+
+    #[test]
+    fn known_color() {
+        let red = Pixel::from(Rgba {
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255,
+        });
+        let image = Image::new(2, 2, [red; 4]);
+        let res = image.to_ansi(2);
+        assert!(res.contains("\x1b[48;2;255;0;0m"));
+        assert!(res.contains("\x1b[38;2;255;0;0m"));
+        assert_eq!(res.lines().count(), 1);
+    }
+
+    #[test]
+    fn empty_output() {
+        let image = Image::new(0, 0, []);
+        assert_eq!(image.to_ansi(10), "");
+
+        let image = Image::new(1, 1, [Pixel::from(0)]);
+        assert_eq!(image.to_ansi(0), "");
+    }
+}
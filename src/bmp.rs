@@ -0,0 +1,98 @@
+use alloc::vec::Vec;
+
+use crate::Image;
+
+const FILE_HEADER_SIZE: u32 = 14;
+const INFO_HEADER_SIZE: u32 = 40;
+const BITS_PER_PIXEL: u16 = 32;
+// 72 DPI
+const PIXELS_PER_METER: i32 = 2835;
+
+impl Image {
+    /// Encodes the image as a 32 bit BGRA BMP, which keeps the alpha channel.
+    ///
+    /// Panics if the width or height doesn't fit in an i32 or if the file would
+    /// be larger than 4 GiB.
+    pub fn write_bmp(&self) -> Vec<u8> {
+        let width = i32::try_from(self.width()).expect("Image::write_bmp() width overflows i32");
+        let height = i32::try_from(self.height()).expect("Image::write_bmp() height overflows i32");
+        let image_size = self
+            .len()
+            .checked_mul(4)
+            .and_then(|size| u32::try_from(size).ok())
+            .expect("Image::write_bmp() image size overflows u32");
+        let offset = FILE_HEADER_SIZE + INFO_HEADER_SIZE;
+        let file_size = offset
+            .checked_add(image_size)
+            .expect("Image::write_bmp() file size overflows u32");
+
+        let mut bmp = Vec::with_capacity(file_size as usize);
+
+        // BITMAPFILEHEADER
+        bmp.extend_from_slice(b"BM");
+        bmp.extend_from_slice(&file_size.to_le_bytes());
+        bmp.extend_from_slice(&0u32.to_le_bytes());
+        bmp.extend_from_slice(&offset.to_le_bytes());
+
+        // BITMAPINFOHEADER
+        bmp.extend_from_slice(&INFO_HEADER_SIZE.to_le_bytes());
+        bmp.extend_from_slice(&width.to_le_bytes());
+        // A positive height means the rows are stored bottom-up
+        bmp.extend_from_slice(&height.to_le_bytes());
+        bmp.extend_from_slice(&1u16.to_le_bytes());
+        bmp.extend_from_slice(&BITS_PER_PIXEL.to_le_bytes());
+        bmp.extend_from_slice(&0u32.to_le_bytes());
+        bmp.extend_from_slice(&image_size.to_le_bytes());
+        bmp.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+        bmp.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+        bmp.extend_from_slice(&0u32.to_le_bytes());
+        bmp.extend_from_slice(&0u32.to_le_bytes());
+
+        // 32 bits pixels are always 4 bytes aligned, rows don't need padding
+        if self.width() != 0 {
+            for row in self.chunks_exact(self.width()).rev() {
+                for pixel in row {
+                    let rgba = pixel.rgba();
+                    bmp.extend_from_slice(&[rgba.b, rgba.g, rgba.r, rgba.a]);
+                }
+            }
+        }
+
+        bmp
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::image::rgba;
+
+    #[test]
+    fn header_and_pixel_order() {
+        let image = Image::new(
+            2,
+            2,
+            [
+                rgba(1, 2, 3, 4),
+                rgba(5, 6, 7, 8),
+                rgba(9, 10, 11, 12),
+                rgba(13, 14, 15, 16),
+            ],
+        );
+        let bmp = image.write_bmp();
+
+        assert_eq!(&bmp[0..2], b"BM");
+        assert_eq!(bmp.len(), 54 + 16);
+        assert_eq!(u32::from_le_bytes(bmp[2..6].try_into().unwrap()), 70);
+        assert_eq!(u32::from_le_bytes(bmp[10..14].try_into().unwrap()), 54);
+        assert_eq!(u32::from_le_bytes(bmp[14..18].try_into().unwrap()), 40);
+        assert_eq!(i32::from_le_bytes(bmp[18..22].try_into().unwrap()), 2);
+        assert_eq!(i32::from_le_bytes(bmp[22..26].try_into().unwrap()), 2);
+        assert_eq!(u16::from_le_bytes(bmp[28..30].try_into().unwrap()), 32);
+        assert_eq!(u32::from_le_bytes(bmp[34..38].try_into().unwrap()), 16);
+
+        // Bottom row first, in BGRA order
+        assert_eq!(&bmp[54..62], [11, 10, 9, 12, 15, 14, 13, 16]);
+        assert_eq!(&bmp[62..70], [3, 2, 1, 4, 7, 6, 5, 8]);
+    }
+}
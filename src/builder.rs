@@ -0,0 +1,107 @@
+use alloc::vec::Vec;
+use core::{error::Error, fmt::Display};
+
+use crate::{Image, Pixel};
+
+/// Collects the pixels of an image one by one, in row major order
+#[derive(Debug, Clone)]
+pub struct ImageBuilder {
+    width: usize,
+    height: usize,
+    pixels: Vec<Pixel>,
+}
+
+impl ImageBuilder {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, pixel: Pixel) -> &mut Self {
+        self.pixels.push(pixel);
+        self
+    }
+
+    pub fn build(self) -> Result<Image, BuildError> {
+        let expected = self
+            .width
+            .checked_mul(self.height)
+            .ok_or(BuildError::DimensionOverflow)?;
+        if self.pixels.len() != expected {
+            return Err(BuildError::CountMismatch {
+                expected,
+                got: self.pixels.len(),
+            });
+        }
+        Ok(Image::new(self.width, self.height, self.pixels))
+    }
+}
+
+impl Extend<Pixel> for ImageBuilder {
+    fn extend<T: IntoIterator<Item = Pixel>>(&mut self, iter: T) {
+        self.pixels.extend(iter);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    DimensionOverflow,
+    CountMismatch { expected: usize, got: usize },
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BuildError::DimensionOverflow => write!(f, "width * height overflowed"),
+            BuildError::CountMismatch { expected, got } => {
+                write!(f, "expected width * height = {expected} pixels, got {got}")
+            }
+        }
+    }
+}
+
+impl Error for BuildError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn build() {
+        let mut builder = ImageBuilder::new(3, 2);
+        for y in 0..2 {
+            builder.extend((0..3).map(|x| Pixel::from(y * 3 + x)));
+        }
+        let image = builder.build().unwrap();
+        let expected: Vec<Pixel> = (0..6).map(Pixel::from).collect();
+        assert_eq!(image, (3, 2, &expected[..]));
+
+        let mut builder = ImageBuilder::new(1, 1);
+        builder.push(Pixel::from(42));
+        assert_eq!(builder.build().unwrap(), (1, 1, &[Pixel::from(42)][..]));
+    }
+
+    #[test]
+    fn build_errors() {
+        let mut builder = ImageBuilder::new(2, 2);
+        builder
+            .push(Pixel::from(1))
+            .push(Pixel::from(2))
+            .push(Pixel::from(3));
+        assert_eq!(
+            builder.build().unwrap_err(),
+            BuildError::CountMismatch {
+                expected: 4,
+                got: 3
+            }
+        );
+
+        assert_eq!(
+            ImageBuilder::new(usize::MAX, 2).build().unwrap_err(),
+            BuildError::DimensionOverflow
+        );
+    }
+}
@@ -0,0 +1,523 @@
+use crate::{
+    image::{Channel, Rgba},
+    Image,
+};
+
+impl Image {
+    /// Builds an opaque grayscale image out of a single channel
+    pub fn extract_channel(&self, channel: Channel) -> Image {
+        self.map_pixels(|rgba| {
+            let value = rgba.channel(channel);
+            Rgba {
+                r: value,
+                g: value,
+                b: value,
+                a: 255,
+            }
+        })
+    }
+
+    pub fn invert(&mut self) {
+        for pixel in self.iter_mut() {
+            let rgba = pixel.rgba_mut();
+            rgba.r = 255 - rgba.r;
+            rgba.g = 255 - rgba.g;
+            rgba.b = 255 - rgba.b;
+        }
+    }
+
+    /// Adds `delta` to the red, green and blue channels, saturating at 0 and 255
+    pub fn adjust_brightness(&mut self, delta: i16) {
+        let adjust = |value: u8| (value as i16).saturating_add(delta).clamp(0, 255) as u8;
+        self.map_pixels_mut(|rgba| Rgba {
+            r: adjust(rgba.r),
+            g: adjust(rgba.g),
+            b: adjust(rgba.b),
+            a: rgba.a,
+        });
+    }
+
+    /// Scales the distance of the red, green and blue channels to 128 by
+    /// `factor`, saturating at 0 and 255
+    pub fn adjust_contrast(&mut self, factor: f32) {
+        let adjust =
+            |value: u8| (((value as f32 - 128.) * factor + 128.).clamp(0., 255.) + 0.5) as u8;
+        self.map_pixels_mut(|rgba| Rgba {
+            r: adjust(rgba.r),
+            g: adjust(rgba.g),
+            b: adjust(rgba.b),
+            a: rgba.a,
+        });
+    }
+
+    /// Multiplies the red, green and blue channels by the alpha. The colors are
+    /// rounded to u8, so a premultiply_alpha() / unpremultiply_alpha() round trip
+    /// loses precision, and the colors of transparent pixels are lost.
+    pub fn premultiply_alpha(&mut self) {
+        self.map_pixels_mut(|rgba| {
+            let multiply = |value: u8| ((value as u32 * rgba.a as u32 + 127) / 255) as u8;
+            Rgba {
+                r: multiply(rgba.r),
+                g: multiply(rgba.g),
+                b: multiply(rgba.b),
+                a: rgba.a,
+            }
+        });
+    }
+
+    /// Divides the red, green and blue channels by the alpha, fully transparent
+    /// pixels are left untouched. See premultiply_alpha() for the precision loss.
+    pub fn unpremultiply_alpha(&mut self) {
+        self.map_pixels_mut(|rgba| {
+            if rgba.a == 0 {
+                return rgba;
+            }
+            let alpha = rgba.a as u32;
+            let divide = |value: u8| ((value as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+            Rgba {
+                r: divide(rgba.r),
+                g: divide(rgba.g),
+                b: divide(rgba.b),
+                a: rgba.a,
+            }
+        });
+    }
+
+    #[cfg(feature = "std")]
+    /// Gamma encodes the red, green and blue channels: `255 * (v / 255)^(1 / gamma)`
+    pub fn apply_gamma(&mut self, gamma: f32) {
+        let lut: [u8; 256] = core::array::from_fn(|value| {
+            (255. * (value as f32 / 255.).powf(1. / gamma))
+                .clamp(0., 255.)
+                .round() as u8
+        });
+        self.apply_lut(&lut, &lut, &lut);
+    }
+
+    #[cfg(feature = "std")]
+    /// Decodes the sRGB transfer function of the red, green and blue channels
+    pub fn srgb_to_linear(&self) -> Image {
+        let lut: [u8; 256] = core::array::from_fn(|value| {
+            let value = value as f32 / 255.;
+            let linear = if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            };
+            to_u8(linear)
+        });
+        let mut image = self.clone();
+        image.apply_lut(&lut, &lut, &lut);
+        image
+    }
+
+    #[cfg(feature = "std")]
+    /// Encodes the red, green and blue channels with the sRGB transfer function
+    pub fn linear_to_srgb(&self) -> Image {
+        let lut: [u8; 256] = core::array::from_fn(|value| {
+            let value = value as f32 / 255.;
+            let srgb = if value <= 0.0031308 {
+                value * 12.92
+            } else {
+                1.055 * value.powf(1. / 2.4) - 0.055
+            };
+            to_u8(srgb)
+        });
+        let mut image = self.clone();
+        image.apply_lut(&lut, &lut, &lut);
+        image
+    }
+
+    /// Reduces the red, green and blue channels to `levels` evenly spaced values
+    /// from 0 to 255, levels below 2 behave like 2
+    pub fn posterize(&mut self, levels: u8) {
+        let levels = levels.max(2) as usize;
+        let lut: [u8; 256] = core::array::from_fn(|value| {
+            let step = value * levels / 256;
+            ((step * 255 + (levels - 1) / 2) / (levels - 1)) as u8
+        });
+        self.apply_lut(&lut, &lut, &lut);
+    }
+
+    /// Maps the red, green and blue channels through their lookup table, alpha
+    /// is untouched
+    pub fn apply_lut(&mut self, r: &[u8; 256], g: &[u8; 256], b: &[u8; 256]) {
+        self.map_pixels_mut(|rgba| Rgba {
+            r: r[rgba.r as usize],
+            g: g[rgba.g as usize],
+            b: b[rgba.b as usize],
+            a: rgba.a,
+        });
+    }
+
+    /// Opaque black and white image, black where the luminance is below
+    /// `level` and white elsewhere
+    pub fn threshold(&self, level: u8) -> Image {
+        self.map_pixels(|rgba| {
+            let value = if rgba.luminance() < level { 0 } else { 255 };
+            Rgba {
+                r: value,
+                g: value,
+                b: value,
+                a: 255,
+            }
+        })
+    }
+
+    /// threshold() with the level given by otsu_level()
+    pub fn threshold_otsu(&self) -> Image {
+        self.threshold(self.otsu_level())
+    }
+
+    /// Level that best splits the luminance histogram in two classes with
+    /// Otsu's method, the luminances below it form the dark class
+    pub fn otsu_level(&self) -> u8 {
+        let histogram = self.luminance_histogram();
+        let total = self.len() as f64;
+        let total_sum: f64 = (0..256)
+            .map(|value| value as f64 * histogram[value] as f64)
+            .sum();
+
+        let mut best_level = 0;
+        let mut best_variance = 0.;
+        let mut dark_count = 0.;
+        let mut dark_sum = 0.;
+        for (value, &count) in histogram.iter().enumerate().take(255) {
+            dark_count += count as f64;
+            dark_sum += value as f64 * count as f64;
+            let light_count = total - dark_count;
+            if dark_count == 0. || light_count == 0. {
+                continue;
+            }
+
+            let mean_difference = dark_sum / dark_count - (total_sum - dark_sum) / light_count;
+            let variance = dark_count * light_count * mean_difference * mean_difference;
+            if variance > best_variance {
+                best_variance = variance;
+                best_level = value as u8 + 1;
+            }
+        }
+        best_level
+    }
+}
+
+#[cfg(feature = "std")]
+fn to_u8(normalized: f32) -> u8 {
+    (normalized * 255.).clamp(0., 255.).round() as u8
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::image::rgba;
+
+    #[test]
+    fn extract_channel() {
+        let image = Image::new(2, 1, [rgba(1, 2, 3, 4), rgba(5, 6, 7, 8)]);
+        let expected = Image::new(2, 1, [rgba(2, 2, 2, 255), rgba(6, 6, 6, 255)]);
+        assert_eq!(expected, image.extract_channel(Channel::Green));
+
+        let expected = Image::new(2, 1, [rgba(4, 4, 4, 255), rgba(8, 8, 8, 255)]);
+        assert_eq!(expected, image.extract_channel(Channel::Alpha));
+    }
+
+    #[test]
+    fn invert() {
+        let mut image = Image::new(1, 1, [rgba(10, 20, 30, 42)]);
+        image.invert();
+        assert_eq!(Image::new(1, 1, [rgba(245, 235, 225, 42)]), image);
+    }
+
+    #[test]
+    fn adjust_brightness() {
+        let mut image = Image::new(2, 1, [rgba(128, 128, 128, 42), rgba(10, 250, 0, 7)]);
+        image.adjust_brightness(20);
+        assert_eq!(
+            Image::new(2, 1, [rgba(148, 148, 148, 42), rgba(30, 255, 20, 7)]),
+            image
+        );
+
+        image.adjust_brightness(-40);
+        assert_eq!(
+            Image::new(2, 1, [rgba(108, 108, 108, 42), rgba(0, 215, 0, 7)]),
+            image
+        );
+
+        image.adjust_brightness(300);
+        assert_eq!(
+            Image::new(2, 1, [rgba(255, 255, 255, 42), rgba(255, 255, 255, 7)]),
+            image
+        );
+
+        image.adjust_brightness(i16::MIN);
+        assert_eq!(
+            Image::new(2, 1, [rgba(0, 0, 0, 42), rgba(0, 0, 0, 7)]),
+            image
+        );
+    }
+
+    #[test]
+    fn adjust_contrast() {
+        let mut image = Image::new(2, 1, [rgba(128, 138, 118, 42), rgba(0, 200, 255, 7)]);
+        image.adjust_contrast(2.);
+        assert_eq!(
+            Image::new(2, 1, [rgba(128, 148, 108, 42), rgba(0, 255, 255, 7)]),
+            image
+        );
+
+        image.adjust_contrast(0.5);
+        assert_eq!(
+            Image::new(2, 1, [rgba(128, 138, 118, 42), rgba(64, 192, 192, 7)]),
+            image
+        );
+
+        image.adjust_contrast(0.);
+        assert!(image
+            .iter()
+            .all(|pixel| pixel.rgba().r == 128 && pixel.rgba().g == 128));
+    }
+
+    #[test]
+    fn threshold() {
+        let image = Image::new(
+            3,
+            1,
+            [
+                rgba(99, 99, 99, 7),
+                rgba(100, 100, 100, 7),
+                rgba(255, 0, 0, 7),
+            ],
+        );
+        let expected = Image::new(
+            3,
+            1,
+            [
+                rgba(0, 0, 0, 255),
+                rgba(255, 255, 255, 255),
+                rgba(0, 0, 0, 255),
+            ],
+        );
+        assert_eq!(expected, image.threshold(100));
+    }
+
+    #[test]
+    fn threshold_otsu() {
+        let gray = |value| rgba(value, value, value, 255);
+        let image = Image::new(
+            4,
+            2,
+            [
+                gray(20),
+                gray(30),
+                gray(25),
+                gray(200),
+                gray(210),
+                gray(190),
+                gray(30),
+                gray(205),
+            ],
+        );
+
+        let level = image.otsu_level();
+        assert!((31..=190).contains(&level), "level {level}");
+        let binary = image.threshold_otsu();
+        assert_eq!(binary, image.threshold(level));
+        let whites: Vec<bool> = binary.iter().map(|pixel| pixel.rgba().r == 255).collect();
+        assert_eq!(whites, [false, false, false, true, true, true, false, true]);
+
+        assert_eq!(Image::new(1, 1, [gray(42)]).otsu_level(), 0);
+    }
+
+    #[test]
+    fn premultiply_round_trip() {
+        let mut image = Image::new(
+            3,
+            1,
+            [
+                rgba(200, 101, 3, 128),
+                rgba(10, 20, 30, 0),
+                rgba(1, 2, 255, 255),
+            ],
+        );
+        let original = image.clone();
+
+        image.premultiply_alpha();
+        assert_eq!(
+            Image::new(
+                3,
+                1,
+                [
+                    rgba(100, 51, 2, 128),
+                    rgba(0, 0, 0, 0),
+                    rgba(1, 2, 255, 255)
+                ]
+            ),
+            image
+        );
+
+        image.unpremultiply_alpha();
+        assert_eq!(image[2], original[2]);
+        let res = image[0].rgba();
+        let expected = original[0].rgba();
+        assert!(res.r.abs_diff(expected.r) <= 1);
+        assert!(res.g.abs_diff(expected.g) <= 1);
+        assert!(res.b.abs_diff(expected.b) <= 1);
+        assert_eq!(res.a, 128);
+        assert_eq!(image[1].rgba(), Rgba::TRANSPARENT);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn apply_gamma() {
+        let mut image = Image::new(
+            3,
+            1,
+            [rgba(128, 0, 255, 42), rgba(64, 64, 64, 1), rgba(1, 2, 3, 4)],
+        );
+        let original = image.clone();
+        image.apply_gamma(2.2);
+        assert_eq!(
+            image[0].rgba(),
+            Rgba {
+                r: 186,
+                g: 0,
+                b: 255,
+                a: 42
+            }
+        );
+        assert_eq!(
+            image[1].rgba(),
+            Rgba {
+                r: 136,
+                g: 136,
+                b: 136,
+                a: 1
+            }
+        );
+
+        let mut image = original.clone();
+        image.apply_gamma(1.);
+        assert_eq!(image, original);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn srgb_and_linear() {
+        let image = Image::new(
+            4,
+            1,
+            [
+                rgba(0, 255, 10, 7),
+                rgba(128, 188, 64, 1),
+                rgba(1, 2, 3, 4),
+                rgba(13, 0, 0, 0),
+            ],
+        );
+        let linear = image.srgb_to_linear();
+        // 10 / 255 is under the 0.04045 breakpoint: 10 / 12.92 = 0.77
+        assert_eq!(
+            linear[0].rgba(),
+            Rgba {
+                r: 0,
+                g: 255,
+                b: 1,
+                a: 7
+            }
+        );
+        // ((128 / 255 + 0.055) / 1.055)^2.4 * 255 = 55.1
+        assert_eq!(
+            linear[1].rgba(),
+            Rgba {
+                r: 55,
+                g: 128,
+                b: 13,
+                a: 1
+            }
+        );
+
+        let srgb = image.linear_to_srgb();
+        // 1 / 255 is over the 0.0031308 breakpoint
+        assert_eq!(
+            srgb[2].rgba(),
+            Rgba {
+                r: 13,
+                g: 22,
+                b: 28,
+                a: 4
+            }
+        );
+        assert_eq!(srgb[1].rgba().r, 188);
+        assert_eq!(srgb[3].rgba().r, 64);
+    }
+
+    #[test]
+    fn apply_lut() {
+        let identity: [u8; 256] = core::array::from_fn(|value| value as u8);
+        let inverted: [u8; 256] = core::array::from_fn(|value| 255 - value as u8);
+        let zero = [0; 256];
+
+        let original = Image::new(2, 1, [rgba(10, 20, 30, 42), rgba(0, 128, 255, 7)]);
+        let mut image = original.clone();
+        image.apply_lut(&identity, &identity, &identity);
+        assert_eq!(image, original);
+
+        image.apply_lut(&inverted, &inverted, &inverted);
+        let mut expected = original.clone();
+        expected.invert();
+        assert_eq!(image, expected);
+
+        image.apply_lut(&inverted, &zero, &identity);
+        assert_eq!(
+            Image::new(2, 1, [rgba(10, 0, 225, 42), rgba(0, 0, 0, 7)]),
+            image
+        );
+    }
+
+    #[test]
+    fn posterize() {
+        let mut image = Image::new(
+            3,
+            1,
+            [
+                rgba(100, 200, 127, 42),
+                rgba(128, 0, 255, 7),
+                rgba(64, 191, 192, 0),
+            ],
+        );
+        let original = image.clone();
+        image.posterize(2);
+        assert_eq!(
+            Image::new(
+                3,
+                1,
+                [
+                    rgba(0, 255, 0, 42),
+                    rgba(255, 0, 255, 7),
+                    rgba(0, 255, 255, 0)
+                ]
+            ),
+            image
+        );
+
+        let mut image = original.clone();
+        image.posterize(3);
+        assert_eq!(
+            Image::new(
+                3,
+                1,
+                [
+                    rgba(128, 255, 128, 42),
+                    rgba(128, 0, 255, 7),
+                    rgba(0, 255, 255, 0)
+                ]
+            ),
+            image
+        );
+
+        let mut image = original.clone();
+        image.posterize(0);
+        let mut expected = original.clone();
+        expected.posterize(2);
+        assert_eq!(image, expected);
+    }
+}
@@ -0,0 +1,188 @@
+use alloc::{collections::TryReserveError, vec::Vec};
+use core::{error::Error, fmt::Display};
+
+use crate::{Image, Pixel};
+
+impl Image {
+    /// Places the images side by side from left to right, they all need the
+    /// same height. An empty slice gives an empty image.
+    pub fn concat_horizontal(images: &[Image]) -> Result<Image, ConcatError> {
+        let Some(height) = images.first().map(Image::height) else {
+            return Ok(Image::new(0, 0, []));
+        };
+        if images.iter().any(|image| image.height() != height) {
+            return Err(ConcatError::DimensionMismatch);
+        }
+        let width = images
+            .iter()
+            .try_fold(0usize, |width, image| width.checked_add(image.width()))
+            .ok_or(ConcatError::DimensionOverflow)?;
+
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for image in images {
+                let pixels: &[Pixel] = image;
+                data.extend_from_slice(&pixels[y * image.width()..(y + 1) * image.width()]);
+            }
+        }
+        Ok(Image::new(width, height, data))
+    }
+
+    /// Stacks the images from top to bottom, they all need the same width. An
+    /// empty slice gives an empty image.
+    pub fn concat_vertical(images: &[Image]) -> Result<Image, ConcatError> {
+        let Some(width) = images.first().map(Image::width) else {
+            return Ok(Image::new(0, 0, []));
+        };
+        if images.iter().any(|image| image.width() != width) {
+            return Err(ConcatError::DimensionMismatch);
+        }
+        let height = images
+            .iter()
+            .try_fold(0usize, |height, image| height.checked_add(image.height()))
+            .ok_or(ConcatError::DimensionOverflow)?;
+
+        let mut data = Vec::with_capacity(width * height);
+        for image in images {
+            data.extend_from_slice(image);
+        }
+        Ok(Image::new(width, height, data))
+    }
+
+    /// Repeats the image from the top left corner to fill an `out_width` by
+    /// `out_height` image, the copies on the right and bottom borders are cut
+    pub fn tiled(&self, out_width: usize, out_height: usize) -> Result<Image, TileError> {
+        if self.is_empty() {
+            return Err(TileError::EmptySource);
+        }
+        let size = out_width
+            .checked_mul(out_height)
+            .ok_or(TileError::DimensionOverflow)?;
+
+        let pixels: &[Pixel] = self;
+        let mut data = Vec::new();
+        data.try_reserve_exact(size)
+            .map_err(TileError::FailedToAllocate)?;
+        for y in 0..out_height {
+            let row = &pixels[y % self.height() * self.width()..][..self.width()];
+            data.extend((0..out_width).map(|x| row[x % self.width()]));
+        }
+        Ok(Image::new(out_width, out_height, data))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConcatError {
+    DimensionMismatch,
+    DimensionOverflow,
+}
+
+impl Display for ConcatError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ConcatError::DimensionMismatch => {
+                write!(f, "the images to concatenate don't have the same size")
+            }
+            ConcatError::DimensionOverflow => write!(f, "the concatenated size overflows usize"),
+        }
+    }
+}
+
+impl Error for ConcatError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TileError {
+    EmptySource,
+    DimensionOverflow,
+    FailedToAllocate(TryReserveError),
+}
+
+impl Display for TileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TileError::EmptySource => write!(f, "an image without pixels can't be tiled"),
+            TileError::DimensionOverflow => write!(f, "the tiled size overflows usize"),
+            TileError::FailedToAllocate(err) => {
+                write!(f, "failed to allocate the tiled image: {err}")
+            }
+        }
+    }
+}
+
+impl Error for TileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TileError::FailedToAllocate(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn image(width: usize, height: usize, first: u32) -> Image {
+        let data: Vec<Pixel> = (first..first + (width * height) as u32)
+            .map(Pixel::from)
+            .collect();
+        Image::new(width, height, data)
+    }
+
+    #[test]
+    fn concat_horizontal() {
+        let res = Image::concat_horizontal(&[image(2, 2, 0), image(2, 2, 10)]).unwrap();
+        let expected = [0, 1, 10, 11, 2, 3, 12, 13].map(Pixel::from);
+        assert_eq!(res, (4, 2, &expected[..]));
+
+        let res = Image::concat_horizontal(&[image(1, 2, 0), image(3, 2, 10)]).unwrap();
+        let expected = [0, 10, 11, 12, 1, 13, 14, 15].map(Pixel::from);
+        assert_eq!(res, (4, 2, &expected[..]));
+
+        assert_eq!(
+            Image::concat_horizontal(&[image(2, 2, 0), image(2, 3, 0)]),
+            Err(ConcatError::DimensionMismatch)
+        );
+    }
+
+    #[test]
+    fn concat_vertical() {
+        let res = Image::concat_vertical(&[image(2, 1, 0), image(2, 2, 10)]).unwrap();
+        let expected = [0, 1, 10, 11, 12, 13].map(Pixel::from);
+        assert_eq!(res, (2, 3, &expected[..]));
+
+        assert_eq!(
+            Image::concat_vertical(&[image(2, 2, 0), image(3, 2, 0)]),
+            Err(ConcatError::DimensionMismatch)
+        );
+    }
+
+    #[test]
+    fn tiled() {
+        let checkerboard = Image::new(2, 2, [0, 1, 1, 0].map(Pixel::from));
+        let res = checkerboard.tiled(4, 4).unwrap();
+        let expected = [0, 1, 0, 1, 1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0].map(Pixel::from);
+        assert_eq!(res, (4, 4, &expected[..]));
+
+        let res = image(3, 2, 0).tiled(4, 3).unwrap();
+        let expected = [0, 1, 2, 0, 3, 4, 5, 3, 0, 1, 2, 0].map(Pixel::from);
+        assert_eq!(res, (4, 3, &expected[..]));
+        assert_eq!(image(3, 2, 0).tiled(0, 5).unwrap(), (0, 5, &[][..]));
+
+        assert_eq!(image(0, 2, 0).tiled(4, 4), Err(TileError::EmptySource));
+        assert_eq!(
+            checkerboard.tiled(usize::MAX, 2),
+            Err(TileError::DimensionOverflow)
+        );
+        assert!(matches!(
+            checkerboard.tiled(usize::MAX / 2, 1),
+            Err(TileError::FailedToAllocate(_))
+        ));
+    }
+
+    #[test]
+    fn concat_nothing() {
+        assert_eq!(Image::concat_horizontal(&[]).unwrap(), (0, 0, &[][..]));
+        assert_eq!(Image::concat_vertical(&[]).unwrap(), (0, 0, &[][..]));
+    }
+}
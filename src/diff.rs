@@ -0,0 +1,154 @@
+use core::{error::Error, fmt::Display};
+
+use crate::{Image, Rgba};
+
+/// How much two images of the same size differ, over the four channels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffStats {
+    pub max_channel_delta: u8,
+    pub mean_squared_error: f64,
+    pub differing_pixels: usize,
+}
+
+/// The compared images don't have the same width and height
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DimensionMismatch;
+
+impl Display for DimensionMismatch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "the images don't have the same dimensions")
+    }
+}
+
+impl Error for DimensionMismatch {}
+
+impl Image {
+    pub fn diff(&self, other: &Image) -> Result<DiffStats, DimensionMismatch> {
+        check_dimensions(self, other)?;
+
+        let mut max_channel_delta = 0;
+        let mut squared_error_sum = 0u64;
+        let mut differing_pixels = 0;
+        for (a, b) in self.iter().zip(other.iter()) {
+            let deltas = channel_deltas(a.rgba(), b.rgba());
+            if deltas != [0; 4] {
+                differing_pixels += 1;
+            }
+            for delta in deltas {
+                max_channel_delta = max_channel_delta.max(delta);
+                squared_error_sum += delta as u64 * delta as u64;
+            }
+        }
+
+        let samples = self.len() * 4;
+        Ok(DiffStats {
+            max_channel_delta,
+            mean_squared_error: if samples == 0 {
+                0.
+            } else {
+                squared_error_sum as f64 / samples as f64
+            },
+            differing_pixels,
+        })
+    }
+
+    /// Copy of `self` where the pixels that differ from `other` are replaced by
+    /// `highlight`
+    pub fn diff_visual(&self, other: &Image, highlight: Rgba) -> Result<Image, DimensionMismatch> {
+        check_dimensions(self, other)?;
+
+        let mut image = self.clone();
+        for (pixel, other) in image.iter_mut().zip(other.iter()) {
+            if pixel != other {
+                *pixel = highlight.into();
+            }
+        }
+        Ok(image)
+    }
+}
+
+fn check_dimensions(a: &Image, b: &Image) -> Result<(), DimensionMismatch> {
+    if a.width() != b.width() || a.height() != b.height() {
+        return Err(DimensionMismatch);
+    }
+    Ok(())
+}
+
+fn channel_deltas(a: Rgba, b: Rgba) -> [u8; 4] {
+    [
+        a.r.abs_diff(b.r),
+        a.g.abs_diff(b.g),
+        a.b.abs_diff(b.b),
+        a.a.abs_diff(b.a),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::image::rgba;
+
+    #[test]
+    fn diff_nearly_identical() {
+        let image = Image::new(2, 1, [rgba(10, 20, 30, 255), rgba(40, 50, 60, 255)]);
+        let other = Image::new(2, 1, [rgba(10, 20, 30, 255), rgba(41, 50, 57, 255)]);
+
+        let stats = image.diff(&other).unwrap();
+        assert_eq!(
+            stats,
+            DiffStats {
+                max_channel_delta: 3,
+                mean_squared_error: 10. / 8.,
+                differing_pixels: 1,
+            }
+        );
+        assert_eq!(other.diff(&image).unwrap(), stats);
+
+        let stats = image.diff(&image).unwrap();
+        assert_eq!(stats.max_channel_delta, 0);
+        assert_eq!(stats.mean_squared_error, 0.);
+        assert_eq!(stats.differing_pixels, 0);
+    }
+
+    #[test]
+    fn diff_dimension_mismatch() {
+        let image = Image::new(2, 1, [rgba(0, 0, 0, 0); 2]);
+        let other = Image::new(1, 2, [rgba(0, 0, 0, 0); 2]);
+        assert_eq!(image.diff(&other), Err(DimensionMismatch));
+
+        let empty = Image::new(0, 0, []);
+        assert_eq!(empty.diff(&empty).unwrap().mean_squared_error, 0.);
+    }
+
+    #[test]
+    fn diff_visual() {
+        let image = Image::new(
+            2,
+            2,
+            [
+                rgba(1, 2, 3, 4),
+                rgba(5, 6, 7, 8),
+                rgba(9, 10, 11, 12),
+                rgba(13, 14, 15, 16),
+            ],
+        );
+        let mut other = image.clone();
+        other[2] = rgba(9, 10, 11, 13);
+
+        let res = image.diff_visual(&other, Rgba::RED).unwrap();
+        let expected = Image::new(
+            2,
+            2,
+            [
+                rgba(1, 2, 3, 4),
+                rgba(5, 6, 7, 8),
+                Rgba::RED.into(),
+                rgba(13, 14, 15, 16),
+            ],
+        );
+        assert_eq!(res, expected);
+
+        let other = Image::new(1, 4, [rgba(0, 0, 0, 0); 4]);
+        assert_eq!(image.diff_visual(&other, Rgba::RED), Err(DimensionMismatch));
+    }
+}
@@ -0,0 +1,334 @@
+use core::{error::Error, fmt::Display, ops::Range};
+
+use crate::{
+    image::{Pixel, Rgba},
+    Image,
+};
+
+/// The coordinates aren't all inside of the image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds;
+
+impl Display for OutOfBounds {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "coordinates out of the image bounds")
+    }
+}
+
+impl Error for OutOfBounds {}
+
+impl Image {
+    /// Alpha composites `top` over `self` with its top left corner at `(x, y)`,
+    /// the parts of `top` falling outside of `self` are clipped.
+    ///
+    /// Both images need meaningful alpha values, images parsed from formats
+    /// without an alpha channel get [`DEFAULT_ALPHA_VALUE`] which is opaque,
+    /// so `top` fully replaces what it covers unless its alpha is set.
+    ///
+    /// [`DEFAULT_ALPHA_VALUE`]: crate::DEFAULT_ALPHA_VALUE
+    pub fn blend_over(&mut self, top: &Image, x: usize, y: usize) {
+        let width = top.width().min(self.width().saturating_sub(x));
+        let height = top.height().min(self.height().saturating_sub(y));
+
+        for top_y in 0..height {
+            for top_x in 0..width {
+                let index = (y + top_y) * self.width() + x + top_x;
+                let source = top[top_y * top.width() + top_x].rgba();
+                let destination = self[index].rgba_mut();
+                *destination = source_over(source, *destination);
+            }
+        }
+    }
+
+    /// Sets the `width` x `height` rectangle with its top left corner at
+    /// `(x, y)` to `color`. Nothing is drawn and OutOfBounds is returned when
+    /// the rectangle doesn't fit in the image.
+    pub fn fill_rect(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        color: Rgba,
+    ) -> Result<(), OutOfBounds> {
+        let x_end = x.checked_add(width).ok_or(OutOfBounds)?;
+        let y_end = y.checked_add(height).ok_or(OutOfBounds)?;
+        if x_end > self.width() || y_end > self.height() {
+            return Err(OutOfBounds);
+        }
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        let image_width = self.width();
+        let pixels: &mut [Pixel] = self;
+        for row in pixels.chunks_exact_mut(image_width).skip(y).take(height) {
+            row[x..x_end].fill(color.into());
+        }
+        Ok(())
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Rgba) -> Result<(), OutOfBounds> {
+        if x >= self.width() || y >= self.height() {
+            return Err(OutOfBounds);
+        }
+        let index = y * self.width() + x;
+        self[index] = color.into();
+        Ok(())
+    }
+
+    /// Bresenham line from `(x0, y0)` to `(x1, y1)`, both ends included. The
+    /// points of the line outside of the image are skipped, without being
+    /// walked, so the cost only depends on the size of the image.
+    pub fn draw_line(&mut self, x0: usize, y0: usize, x1: usize, y1: usize, color: Rgba) {
+        // Each step moves the major axis by one, and the minor axis by the
+        // rounded slope so far. The point of any step is computed directly,
+        // which keeps the slope of the whole segment for the clipped one.
+        let (dx, dy) = (x0.abs_diff(x1) as u128, y0.abs_diff(y1) as u128);
+        let (major, minor) = (dx.max(dy), dx.min(dy));
+        let minor_steps = |step: u128| match major {
+            0 => 0,
+            // Can't overflow: minor * step <= (2^64 - 1)^2
+            _ => (minor * step + major / 2) / major,
+        };
+        let point = |step: u128| {
+            let (x_steps, y_steps) = if dx >= dy {
+                (step, minor_steps(step))
+            } else {
+                (minor_steps(step), step)
+            };
+            (
+                line_coordinate(x0, x1, x_steps),
+                line_coordinate(y0, y1, y_steps),
+            )
+        };
+
+        // Both coordinates are monotonic along the line, so the steps inside
+        // of the image are a range
+        let x_steps = steps_inside(major, |step| point(step).0, x0 <= x1, self.width());
+        let y_steps = steps_inside(major, |step| point(step).1, y0 <= y1, self.height());
+        for step in x_steps.start.max(y_steps.start)..x_steps.end.min(y_steps.end) {
+            let (x, y) = point(step);
+            let _ = self.set_pixel(x as usize, y as usize, color);
+        }
+    }
+}
+
+fn line_coordinate(start: usize, end: usize, steps: u128) -> i128 {
+    if start <= end {
+        start as i128 + steps as i128
+    } else {
+        start as i128 - steps as i128
+    }
+}
+
+/// The steps of `0..=last` whose coordinate is in `0..limit`
+fn steps_inside(
+    last: u128,
+    coordinate: impl Fn(u128) -> i128,
+    increasing: bool,
+    limit: usize,
+) -> Range<u128> {
+    let first_step = |entered: fn(i128, i128) -> bool| {
+        let (mut low, mut high) = (0, last + 1);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if entered(coordinate(middle), limit as i128) {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+        low
+    };
+    if increasing {
+        first_step(|coordinate, _| coordinate >= 0)..first_step(|coordinate, limit| {
+            coordinate >= limit
+        })
+    } else {
+        first_step(|coordinate, limit| coordinate < limit)..first_step(|coordinate, _| {
+            coordinate < 0
+        })
+    }
+}
+
+fn source_over(top: Rgba, bottom: Rgba) -> Rgba {
+    let alpha = top.a as u32;
+    let blend = |top: u8, bottom: u8| {
+        ((top as u32 * alpha + bottom as u32 * (255 - alpha) + 127) / 255) as u8
+    };
+    Rgba {
+        r: blend(top.r, bottom.r),
+        g: blend(top.g, bottom.g),
+        b: blend(top.b, bottom.b),
+        a: (alpha + (bottom.a as u32 * (255 - alpha) + 127) / 255) as u8,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::image::rgba;
+
+    #[test]
+    fn blend_red_over_white() {
+        let mut image = Image::new(2, 2, [rgba(255, 255, 255, 255); 4]);
+        let top = Image::new(2, 2, [rgba(255, 0, 0, 128); 4]);
+        image.blend_over(&top, 1, 1);
+
+        let white = rgba(255, 255, 255, 255);
+        let expected = Image::new(2, 2, [white, white, white, rgba(255, 127, 127, 255)]);
+        assert_eq!(expected, image);
+    }
+
+    #[test]
+    fn blend_clipped() {
+        let mut image = Image::new(1, 1, [rgba(0, 0, 0, 0)]);
+        let top = Image::new(1, 1, [rgba(10, 20, 30, 255)]);
+        image.blend_over(&top, 5, 0);
+        assert_eq!(Image::new(1, 1, [rgba(0, 0, 0, 0)]), image);
+
+        image.blend_over(&top, 0, 0);
+        assert_eq!(Image::new(1, 1, [rgba(10, 20, 30, 255)]), image);
+    }
+
+    #[test]
+    fn fill_rect_center() {
+        let mut image = Image::new(4, 4, [rgba(0, 0, 0, 255); 16]);
+        image.fill_rect(1, 1, 2, 2, Rgba::RED).unwrap();
+
+        for y in 0..4 {
+            for x in 0..4 {
+                let expected = if (1..3).contains(&x) && (1..3).contains(&y) {
+                    Rgba::RED
+                } else {
+                    Rgba::BLACK
+                };
+                assert_eq!(image[y * 4 + x].rgba(), expected, "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn fill_rect_out_of_bounds() {
+        let mut image = Image::new(4, 4, [rgba(0, 0, 0, 255); 16]);
+        let copy = image.clone();
+        assert_eq!(image.fill_rect(3, 0, 2, 1, Rgba::RED), Err(OutOfBounds));
+        assert_eq!(image.fill_rect(0, 2, 1, 3, Rgba::RED), Err(OutOfBounds));
+        assert_eq!(
+            image.fill_rect(usize::MAX, 0, 2, 1, Rgba::RED),
+            Err(OutOfBounds)
+        );
+        assert_eq!(image, copy);
+
+        assert_eq!(image.fill_rect(4, 4, 0, 0, Rgba::RED), Ok(()));
+        image.fill_rect(0, 0, 4, 4, Rgba::RED).unwrap();
+        assert!(image.iter().all(|pixel| pixel.rgba() == Rgba::RED));
+    }
+
+    fn set_pixels(image: &Image) -> Vec<(usize, usize)> {
+        (0..image.len())
+            .filter(|&index| image[index].rgba() == Rgba::RED)
+            .map(|index| (index % image.width(), index / image.width()))
+            .collect()
+    }
+
+    #[test]
+    fn set_pixel() {
+        let mut image = Image::new(2, 2, [rgba(0, 0, 0, 255); 4]);
+        assert_eq!(image.set_pixel(1, 0, Rgba::RED), Ok(()));
+        assert_eq!(image.set_pixel(2, 0, Rgba::RED), Err(OutOfBounds));
+        assert_eq!(image.set_pixel(0, 2, Rgba::RED), Err(OutOfBounds));
+        assert_eq!(set_pixels(&image), [(1, 0)]);
+    }
+
+    #[test]
+    fn draw_lines() {
+        let black = [rgba(0, 0, 0, 255); 25];
+
+        let mut image = Image::new(5, 5, black);
+        image.draw_line(0, 0, 4, 4, Rgba::RED);
+        assert_eq!(set_pixels(&image), [(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)]);
+
+        let mut reversed = Image::new(5, 5, black);
+        reversed.draw_line(4, 4, 0, 0, Rgba::RED);
+        assert_eq!(reversed, image);
+
+        let mut image = Image::new(5, 5, black);
+        image.draw_line(4, 0, 0, 2, Rgba::RED);
+        assert_eq!(set_pixels(&image), [(4, 0), (2, 1), (3, 1), (0, 2), (1, 2)]);
+
+        let mut image = Image::new(5, 5, black);
+        image.draw_line(1, 4, 1, 2, Rgba::RED);
+        image.draw_line(0, 0, 2, 0, Rgba::RED);
+        assert_eq!(
+            set_pixels(&image),
+            [(0, 0), (1, 0), (2, 0), (1, 2), (1, 3), (1, 4)]
+        );
+
+        let mut image = Image::new(5, 5, black);
+        image.draw_line(3, 3, 7, 7, Rgba::RED);
+        image.draw_line(2, 2, 2, 2, Rgba::RED);
+        assert_eq!(set_pixels(&image), [(2, 2), (3, 3), (4, 4)]);
+    }
+
+    /// The walk over every point of the line that draw_line() skips
+    fn walked_line(x0: usize, y0: usize, x1: usize, y1: usize) -> Vec<(usize, usize)> {
+        let (mut x, mut y) = (x0 as i64, y0 as i64);
+        let (x1, y1) = (x1 as i64, y1 as i64);
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let mut error = dx + dy;
+        let mut points = Vec::new();
+        loop {
+            if (0..5).contains(&x) && (0..4).contains(&y) {
+                points.push((x as usize, y as usize));
+            }
+            if x == x1 && y == y1 {
+                break;
+            }
+            let doubled_error = 2 * error;
+            if doubled_error >= dy {
+                error += dy;
+                x += if x < x1 { 1 } else { -1 };
+            }
+            if doubled_error <= dx {
+                error += dx;
+                y += if y < y1 { 1 } else { -1 };
+            }
+        }
+        points.sort_by_key(|&(x, y)| (y, x));
+        points
+    }
+
+    #[test]
+    fn clipped_lines_match_the_walked_ones() {
+        let black = [rgba(0, 0, 0, 255); 20];
+        for (x0, y0, x1, y1) in (0..81 * 81).map(|i| (i % 9, i / 9 % 9, i / 81 % 9, i / 729)) {
+            let mut image = Image::new(5, 4, black);
+            image.draw_line(x0, y0, x1, y1, Rgba::RED);
+            assert_eq!(
+                set_pixels(&image),
+                walked_line(x0, y0, x1, y1),
+                "({x0}, {y0}) to ({x1}, {y1})"
+            );
+        }
+    }
+
+    #[test]
+    fn far_away_endpoints() {
+        let mut image = Image::new(3, 1, [rgba(0, 0, 0, 255); 3]);
+        image.draw_line(0, 0, 1 << 40, (1 << 40) / 3, Rgba::RED);
+        assert_eq!(set_pixels(&image), [(0, 0), (1, 0)]);
+
+        let mut image = Image::new(3, 3, [rgba(0, 0, 0, 255); 9]);
+        image.draw_line(usize::MAX, usize::MAX, 0, 0, Rgba::RED);
+        image.draw_line(usize::MAX, 0, 0, usize::MAX, Rgba::RED);
+        assert_eq!(set_pixels(&image), [(0, 0), (1, 1), (2, 2)]);
+
+        let mut image = Image::new(3, 3, [rgba(0, 0, 0, 255); 9]);
+        image.draw_line(1, usize::MAX, 1, 0, Rgba::RED);
+        image.draw_line(usize::MAX - 5, 5, usize::MAX, 0, Rgba::RED);
+        assert_eq!(set_pixels(&image), [(1, 0), (1, 1), (1, 2)]);
+    }
+}
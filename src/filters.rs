@@ -0,0 +1,443 @@
+use alloc::vec::Vec;
+
+use crate::{
+    image::{Pixel, Rgba},
+    Image,
+};
+
+impl Image {
+    pub fn pixelate(&mut self, block: usize) {
+        if block <= 1 {
+            return;
+        }
+
+        let width = self.width();
+        let height = self.height();
+        let pixels: &mut [Pixel] = self;
+        for block_y in (0..height).step_by(block) {
+            let block_height = block.min(height - block_y);
+            for block_x in (0..width).step_by(block) {
+                let block_width = block.min(width - block_x);
+
+                let mut sum = [0u64; 4];
+                for y in block_y..block_y + block_height {
+                    for pixel in &pixels[y * width + block_x..y * width + block_x + block_width] {
+                        let rgba = pixel.rgba();
+                        sum[0] += rgba.r as u64;
+                        sum[1] += rgba.g as u64;
+                        sum[2] += rgba.b as u64;
+                        sum[3] += rgba.a as u64;
+                    }
+                }
+
+                let count = (block_width * block_height) as u64;
+                let average = Rgba {
+                    r: ((sum[0] + count / 2) / count) as u8,
+                    g: ((sum[1] + count / 2) / count) as u8,
+                    b: ((sum[2] + count / 2) / count) as u8,
+                    a: ((sum[3] + count / 2) / count) as u8,
+                };
+                for y in block_y..block_y + block_height {
+                    pixels[y * width + block_x..y * width + block_x + block_width]
+                        .fill(average.into());
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    /// Treats the luminance as a heightmap and encodes its tangent space normals
+    /// into rgb with a 0.5 bias, a flat heightmap gives `(128, 128, 255)`
+    pub fn normal_map(&self, strength: f32) -> Image {
+        let luminance = self.luminance_buffer();
+        let mut data = Vec::with_capacity(self.len());
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let (gx, gy) = sobel_gradients(&luminance, self.width(), self.height(), x, y);
+                let nx = -(gx as f32) / 255. * strength;
+                let ny = -(gy as f32) / 255. * strength;
+                let length = (nx * nx + ny * ny + 1.).sqrt();
+                let encode = |value: f32| ((value / length * 0.5 + 0.5) * 255. + 0.5) as u8;
+                data.push(Pixel::from(Rgba {
+                    r: encode(nx),
+                    g: encode(ny),
+                    b: encode(1.),
+                    a: 255,
+                }));
+            }
+        }
+
+        Image::new(self.width(), self.height(), data)
+    }
+
+    #[cfg(feature = "std")]
+    /// Opaque grayscale magnitude of the Sobel gradients of the luminance,
+    /// clamped to 255
+    pub fn sobel(&self) -> Image {
+        let luminance = self.luminance_buffer();
+        let mut data = Vec::with_capacity(self.len());
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let (gx, gy) = sobel_gradients(&luminance, self.width(), self.height(), x, y);
+                let magnitude = ((gx * gx + gy * gy) as f32).sqrt().min(255.) as u8;
+                data.push(Pixel::from(Rgba::rgb(magnitude, magnitude, magnitude)));
+            }
+        }
+
+        Image::new(self.width(), self.height(), data)
+    }
+
+    #[cfg(feature = "std")]
+    fn luminance_buffer(&self) -> Vec<u8> {
+        self.iter().map(|pixel| pixel.rgba().luminance()).collect()
+    }
+}
+
+// Sample coordinates outside of the image are clamped to the border
+#[cfg(feature = "std")]
+fn sobel_gradients(
+    luminance: &[u8],
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+) -> (i32, i32) {
+    let sample = |dx: isize, dy: isize| {
+        let x = x.saturating_add_signed(dx).min(width - 1);
+        let y = y.saturating_add_signed(dy).min(height - 1);
+        luminance[y * width + x] as i32
+    };
+
+    let gx = sample(1, -1) + 2 * sample(1, 0) + sample(1, 1)
+        - sample(-1, -1)
+        - 2 * sample(-1, 0)
+        - sample(-1, 1);
+    let gy = sample(-1, 1) + 2 * sample(0, 1) + sample(1, 1)
+        - sample(-1, -1)
+        - 2 * sample(0, -1)
+        - sample(1, -1);
+    (gx, gy)
+}
+
+impl Image {
+    /// Averages each pixel with the `radius` pixels around it horizontally then
+    /// vertically, the borders are extended
+    pub fn box_blur(&self, radius: usize) -> Image {
+        if radius == 0 {
+            return self.clone();
+        }
+        separable(self, |line| box_blur_line(line, radius))
+    }
+
+    #[cfg(feature = "std")]
+    /// Convolves with a gaussian kernel of standard deviation `sigma` cut at
+    /// 3 sigma, horizontally then vertically, the borders are extended.
+    /// A `sigma` that is not strictly positive and finite (NaN, infinities)
+    /// returns a copy of the image
+    pub fn gaussian_blur(&self, sigma: f32) -> Image {
+        if !sigma.is_finite() || sigma <= 0. {
+            return self.clone();
+        }
+
+        let sigma = f64::from(sigma);
+        let cut = (sigma * 3.).ceil();
+        // Offsets of at least the longest line only ever read a border pixel,
+        // so the kernel stops there and the weights past it are added to its
+        // two ends. The cost doesn't grow with sigma past the image size.
+        let radius = cut.min(self.width().max(self.height()) as f64) as isize;
+        let weight = |x: f64| (-(x * x) / (2. * sigma * sigma)).exp();
+        let mut kernel: Vec<f64> = (-radius..=radius).map(|x| weight(x as f64)).collect();
+        let tail = gaussian_tail(sigma, radius as f64 + 1., cut, weight);
+        kernel[0] += tail;
+        kernel[2 * radius as usize] += tail;
+        let total: f64 = kernel.iter().sum();
+        let kernel: Vec<f32> = kernel
+            .iter()
+            .map(|weight| (weight / total) as f32)
+            .collect();
+
+        separable(self, |line| {
+            (0..line.len() as isize)
+                .map(|x| {
+                    let mut sum = [0f32; 4];
+                    for (offset, weight) in (-radius..=radius).zip(&kernel) {
+                        let rgba = clamped(line, x + offset);
+                        sum[0] += rgba.r as f32 * weight;
+                        sum[1] += rgba.g as f32 * weight;
+                        sum[2] += rgba.b as f32 * weight;
+                        sum[3] += rgba.a as f32 * weight;
+                    }
+                    let [r, g, b, a] = sum.map(|value| (value.clamp(0., 255.) + 0.5) as u8);
+                    Rgba { r, g, b, a }
+                })
+                .collect()
+        })
+    }
+}
+
+/// Applies `blur_line` to every row, then to every column of the result
+fn separable(image: &Image, mut blur_line: impl FnMut(&[Rgba]) -> Vec<Rgba>) -> Image {
+    let width = image.width();
+    let height = image.height();
+    let mut res = image.clone();
+    if width == 0 || height == 0 {
+        return res;
+    }
+
+    let mut line = Vec::with_capacity(width.max(height));
+    for y in 0..height {
+        line.clear();
+        line.extend((0..width).map(|x| res[y * width + x].rgba()));
+        for (x, rgba) in blur_line(&line).into_iter().enumerate() {
+            res[y * width + x] = rgba.into();
+        }
+    }
+    for x in 0..width {
+        line.clear();
+        line.extend((0..height).map(|y| res[y * width + x].rgba()));
+        for (y, rgba) in blur_line(&line).into_iter().enumerate() {
+            res[y * width + x] = rgba.into();
+        }
+    }
+    res
+}
+
+/// Sum of `weight(x)` for the integer `x` in `from..=to`. Short tails are
+/// summed, long ones are approximated by the integral of the gaussian over
+/// `from - 0.5..to + 0.5`, which is off by less than a u8 step
+#[cfg(feature = "std")]
+fn gaussian_tail(sigma: f64, from: f64, to: f64, weight: impl Fn(f64) -> f64) -> f64 {
+    const MAX_SUMMED: f64 = 65536.;
+    if to < from {
+        return 0.;
+    }
+    if to - from < MAX_SUMMED {
+        return (0..=(to - from) as u32)
+            .map(|step| weight(from + f64::from(step)))
+            .sum();
+    }
+
+    let scale = sigma * core::f64::consts::SQRT_2;
+    let erfc_from = erfc((from - 0.5) / scale);
+    let erfc_to = erfc((to + 0.5) / scale);
+    scale * core::f64::consts::PI.sqrt() / 2. * (erfc_from - erfc_to)
+}
+
+/// Complementary error function of a positive `x`, Abramowitz and Stegun
+/// 7.1.26, the absolute error is below 1.5e-7
+#[cfg(feature = "std")]
+fn erfc(x: f64) -> f64 {
+    let t = 1. / (1. + 0.3275911 * x);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    poly * (-x * x).exp()
+}
+
+#[cfg(feature = "std")]
+fn clamped(line: &[Rgba], index: isize) -> Rgba {
+    line[index.clamp(0, line.len() as isize - 1) as usize]
+}
+
+/// Running sum over the window, so each pixel costs the same whatever the
+/// radius. `line` can't be empty. The sums are u128 so that no radius can
+/// overflow them, nor the window length.
+fn box_blur_line(line: &[Rgba], radius: usize) -> Vec<Rgba> {
+    let last = line.len() - 1;
+    let radius = radius as u128;
+    let window = 2 * radius + 1;
+    let at = |index: u128| {
+        let rgba = line[index.min(last as u128) as usize];
+        [rgba.r, rgba.g, rgba.b, rgba.a].map(u128::from)
+    };
+
+    // The window of the first pixel repeats the first pixel radius + 1 times,
+    // and the last one as many times as the window goes past it
+    let mut sum = at(0).map(|channel| channel * (radius + 1));
+    let inside = radius.min(last as u128);
+    for index in 1..=inside {
+        sum = core::array::from_fn(|channel| sum[channel] + at(index)[channel]);
+    }
+    let past_the_end = radius - inside;
+    sum = core::array::from_fn(|channel| sum[channel] + at(last as u128)[channel] * past_the_end);
+
+    let mut res = Vec::with_capacity(line.len());
+    for x in 0..line.len() as u128 {
+        let [r, g, b, a] = sum.map(|channel| ((channel + window / 2) / window) as u8);
+        res.push(Rgba { r, g, b, a });
+        let (entering, leaving) = (at(x + radius + 1), at(x.saturating_sub(radius)));
+        sum = core::array::from_fn(|channel| sum[channel] + entering[channel] - leaving[channel]);
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn gray(value: u8) -> Pixel {
+        Pixel::from(Rgba {
+            r: value,
+            g: value,
+            b: value,
+            a: 255,
+        })
+    }
+
+    #[test]
+    fn pixelate() {
+        #[rustfmt::skip]
+        let mut image = Image::new(4, 4, [
+            0, 10, 100, 100,
+            20, 30, 50, 50,
+            200, 200, 1, 2,
+            200, 200, 3, 4,
+        ].map(gray));
+        image.pixelate(2);
+
+        #[rustfmt::skip]
+        let expected = Image::new(4, 4, [
+            15, 15, 75, 75,
+            15, 15, 75, 75,
+            200, 200, 3, 3,
+            200, 200, 3, 3,
+        ].map(gray));
+        assert_eq!(expected, image);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn flat_normal_map() {
+        let image = Image::new(3, 3, [gray(90); 9]);
+        let res = image.normal_map(4.);
+        let expected = Pixel::from(Rgba {
+            r: 128,
+            g: 128,
+            b: 255,
+            a: 255,
+        });
+        assert_eq!(Image::new(3, 3, [expected; 9]), res);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn slope_normal_map() {
+        let image = Image::new(3, 1, [0, 100, 200].map(gray));
+        let res = image.normal_map(1.);
+        let center = res[1].rgba();
+        assert!(center.r < 128);
+        assert_eq!(center.g, 128);
+    }
+
+    #[test]
+    fn pixelate_smaller_edge_blocks() {
+        let mut image = Image::new(3, 1, [0, 20, 7].map(gray));
+        image.pixelate(2);
+        assert_eq!(Image::new(3, 1, [10, 10, 7].map(gray)), image);
+    }
+
+    fn single_white_pixel() -> Image {
+        let mut image = Image::new(5, 5, [gray(0); 25]);
+        image[2 * 5 + 2] = gray(255);
+        image
+    }
+
+    #[test]
+    fn box_blur_spreads_a_pixel() {
+        let res = single_white_pixel().box_blur(1);
+        for y in 0..5 {
+            for x in 0..5 {
+                let expected = if (1..4).contains(&x) && (1..4).contains(&y) {
+                    28
+                } else {
+                    0
+                };
+                assert_eq!(res[y * 5 + x], gray(expected), "({x}, {y})");
+            }
+        }
+
+        let image = Image::new(3, 1, [gray(0), gray(30), gray(90)]);
+        // The borders are extended: (0 + 0 + 30) / 3, (0 + 30 + 90) / 3, (30 + 90 + 90) / 3
+        assert_eq!(
+            image.box_blur(1),
+            Image::new(3, 1, [gray(10), gray(40), gray(70)])
+        );
+        assert_eq!(image.box_blur(0), image);
+
+        // Radii larger than the image only see more of the extended borders:
+        // (6 * 0 + 30 + 90 + 3 * 90) / 11 for the first pixel of radius 5
+        assert_eq!(
+            image.box_blur(5),
+            Image::new(3, 1, [gray(35), gray(44), gray(52)])
+        );
+        assert_eq!(image.box_blur(usize::MAX), Image::new(3, 1, [gray(45); 3]));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn gaussian_blur_spreads_a_pixel() {
+        let res = single_white_pixel().gaussian_blur(0.8);
+        let value = |x: usize, y: usize| res[y * 5 + x].rgba().r;
+        assert!(value(2, 2) > value(1, 2) && value(1, 2) > value(1, 1));
+        assert!(value(1, 1) > value(0, 0));
+        for (x, y) in [(1, 2), (3, 2), (2, 1), (2, 3)] {
+            assert_eq!(value(x, y), value(1, 2));
+        }
+        assert!(res.iter().all(|pixel| pixel.rgba().a == 255));
+
+        let flat = Image::new(4, 3, [gray(77); 12]);
+        assert_eq!(flat.gaussian_blur(2.), flat);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn gaussian_blur_odd_sigmas() {
+        let image = Image::new(3, 1, [gray(0), gray(30), gray(90)]);
+        for sigma in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY, -1., 0.] {
+            assert_eq!(image.gaussian_blur(sigma), image, "{sigma}");
+        }
+
+        // The kernel stops at the image size, check the folded tail against
+        // the whole kernel
+        let sigma = 5f64;
+        let line: Vec<Rgba> = image.iter().map(|pixel| pixel.rgba()).collect();
+        let expected: Vec<Pixel> = (0..3isize)
+            .map(|x| {
+                let (mut sum, mut total) = (0., 0.);
+                for offset in -15..=15isize {
+                    let weight = (-((offset * offset) as f64) / (2. * sigma * sigma)).exp();
+                    sum += weight * f64::from(clamped(&line, x + offset).r);
+                    total += weight;
+                }
+                gray((sum / total + 0.5) as u8)
+            })
+            .collect();
+        assert_eq!(image.gaussian_blur(5.), Image::new(3, 1, expected));
+
+        // Past the image size almost all the weight is on the two borders
+        for sigma in [1e6, 1e30, f32::MAX] {
+            assert_eq!(
+                image.gaussian_blur(sigma),
+                Image::new(3, 1, [gray(45); 3]),
+                "{sigma}"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn sobel_vertical_edge() {
+        let image = Image::new(4, 3, [gray(0), gray(0), gray(255), gray(255)].repeat(3));
+        let res = image.sobel();
+        for y in 0..3 {
+            let row: Vec<u8> = (0..4).map(|x| res[y * 4 + x].rgba().r).collect();
+            assert_eq!(row, [0, 255, 255, 0]);
+        }
+        assert!(res.iter().all(|pixel| pixel.rgba().a == 255));
+
+        let image = Image::new(5, 1, [gray(0), gray(0), gray(10), gray(20), gray(20)]);
+        let res = image.sobel();
+        let row: Vec<u8> = (0..5).map(|x| res[x].rgba().r).collect();
+        assert_eq!(row, [0, 40, 80, 40, 0]);
+    }
+}
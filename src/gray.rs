@@ -0,0 +1,124 @@
+use alloc::boxed::Box;
+use core::ops::{Index, IndexMut};
+
+use crate::{Image, ImageNewError, Pixel, Rgba};
+
+/// Single channel image, a quarter of the memory of an [`Image`] for the same
+/// grayscale content. PGM files decode to it with
+/// [`parse_pgm_file`](crate::ppm::parse_pgm_file), its values are u8 so 16-bit
+/// samples are scaled down to 8 bits.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct GrayImage {
+    data: Box<[u8]>,
+
+    width: usize,
+    height: usize,
+}
+
+impl GrayImage {
+    /// # Panics
+    ///
+    /// Same conditions as [`Image::new`], see [`GrayImage::try_new`]
+    pub fn new(width: usize, height: usize, data: impl Into<Box<[u8]>>) -> Self {
+        Self::try_new(width, height, data).unwrap_or_else(|err| panic!("GrayImage::new() {err}"))
+    }
+
+    pub fn try_new(
+        width: usize,
+        height: usize,
+        data: impl Into<Box<[u8]>>,
+    ) -> Result<Self, ImageNewError> {
+        let data = data.into();
+
+        let expected = width
+            .checked_mul(height)
+            .ok_or(ImageNewError::DimensionOverflow)?;
+        if expected != data.len() {
+            return Err(ImageNewError::DataLengthMismatch {
+                expected,
+                actual: data.len(),
+            });
+        }
+
+        Ok(Self {
+            data,
+            width,
+            height,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+
+    /// Opaque Image with the gray value in the red, green and blue channels
+    pub fn to_rgba(&self) -> Image {
+        let data: Box<[Pixel]> = self
+            .data
+            .iter()
+            .map(|&value| Pixel::from(Rgba::rgb(value, value, value)))
+            .collect();
+        Image::new(self.width, self.height, data)
+    }
+}
+
+impl Image {
+    /// Keeps the luminance of each pixel, see Rgba::luminance()
+    pub fn to_gray(&self) -> GrayImage {
+        let data: Box<[u8]> = self.iter().map(|pixel| pixel.rgba().luminance()).collect();
+        GrayImage::new(self.width(), self.height(), data)
+    }
+}
+
+impl Index<usize> for GrayImage {
+    type Output = u8;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+impl IndexMut<usize> for GrayImage {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.data[index]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn gray_image() {
+        let mut image = GrayImage::new(3, 1, [0, 128, 255]);
+        assert_eq!((image.width(), image.height()), (3, 1));
+        assert_eq!(image[1], 128);
+        image[1] = 42;
+        assert_eq!(image.pixels(), [0, 42, 255]);
+
+        let rgba = image.to_rgba();
+        let expected = [Rgba::BLACK, Rgba::rgb(42, 42, 42), Rgba::WHITE].map(Pixel::from);
+        assert_eq!(rgba, (3, 1, &expected[..]));
+        assert_eq!(rgba.to_gray(), image);
+
+        assert_eq!(
+            GrayImage::try_new(2, 2, [0; 3]),
+            Err(ImageNewError::DataLengthMismatch {
+                expected: 4,
+                actual: 3
+            })
+        );
+    }
+}
@@ -1,12 +1,22 @@
-use std::{
-    fmt::Debug,
+use alloc::{boxed::Box, collections::TryReserveError, format, string::String, vec::Vec};
+use core::{
+    error::Error,
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
     ops::{Deref, DerefMut, Index, IndexMut},
 };
 
-pub const DEFAULT_ALPHA_VALUE: u8 = 0;
+use crate::ImageRead;
+
+/// Alpha of the pixels decoded from formats without an alpha channel, opaque
+/// so that the decoded colors are visible
+pub const DEFAULT_ALPHA_VALUE: u8 = OPAQUE_ALPHA;
+
+const OPAQUE_ALPHA: u8 = 255;
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rgba {
     pub r: u8,
     pub g: u8,
@@ -21,7 +31,22 @@ pub union Pixel {
     pub color: u32,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Channel {
+    Red,
+    Green,
+    Blue,
+    Alpha,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Channels {
+    Gray,
+    Rgb,
+    Rgba,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Image {
     data: Box<[Pixel]>,
 
@@ -30,21 +55,228 @@ pub struct Image {
 }
 
 impl Image {
+    /// # Panics
+    ///
+    /// When `width * height` overflows usize or isn't the number of pixels in
+    /// `data`, use [`Image::try_new`] to get these cases as an [`ImageNewError`]
+    /// instead. The panic message is `Image::new() ` followed by the error.
     pub fn new(width: usize, height: usize, data: impl Into<Box<[Pixel]>>) -> Self {
+        Self::try_new(width, height, data).unwrap_or_else(|err| panic!("Image::new() {err}"))
+    }
+
+    pub fn try_new(
+        width: usize,
+        height: usize,
+        data: impl Into<Box<[Pixel]>>,
+    ) -> Result<Self, ImageNewError> {
         let data = data.into();
 
-        assert!(
-            width
-                .checked_mul(height)
-                .expect("Image::new() width * height overflowed")
-                == data.len()
-        );
+        let expected = width
+            .checked_mul(height)
+            .ok_or(ImageNewError::DimensionOverflow)?;
+        if expected != data.len() {
+            return Err(ImageNewError::DataLengthMismatch {
+                expected,
+                actual: data.len(),
+            });
+        }
 
-        Self {
+        Ok(Self {
             width,
             height,
             data,
+        })
+    }
+
+    pub fn from_raw(
+        width: usize,
+        height: usize,
+        data: Box<[Pixel]>,
+    ) -> Result<Self, ImageNewError> {
+        Self::try_new(width, height, data)
+    }
+
+    pub fn into_raw(self) -> (usize, usize, Box<[Pixel]>) {
+        (self.width, self.height, self.data)
+    }
+
+    pub fn filled(width: usize, height: usize, pixel: Pixel) -> Result<Self, ImageNewError> {
+        let size = width
+            .checked_mul(height)
+            .ok_or(ImageNewError::DimensionOverflow)?;
+
+        let mut data = Vec::new();
+        data.try_reserve_exact(size)
+            .map_err(ImageNewError::FailedToAllocate)?;
+        data.resize(size, pixel);
+
+        Ok(Self {
+            width,
+            height,
+            data: data.into_boxed_slice(),
+        })
+    }
+
+    /// The pixels of the `Gray` and `Rgb` layouts are opaque, like with
+    /// [`Image::from_rgb_bytes`] and [`GrayImage::to_rgba`](crate::GrayImage::to_rgba)
+    pub fn from_raw_bytes(
+        width: usize,
+        height: usize,
+        channels: Channels,
+        data: &[u8],
+    ) -> Result<Self, FromBytesError> {
+        Self::from_channel_bytes(width, height, channels, data, OPAQUE_ALPHA)
+    }
+
+    pub fn from_rgba_bytes(
+        width: usize,
+        height: usize,
+        bytes: &[u8],
+    ) -> Result<Self, FromBytesError> {
+        Self::from_channel_bytes(width, height, Channels::Rgba, bytes, OPAQUE_ALPHA)
+    }
+
+    /// The pixels are opaque
+    pub fn from_rgb_bytes(
+        width: usize,
+        height: usize,
+        bytes: &[u8],
+    ) -> Result<Self, FromBytesError> {
+        Self::from_channel_bytes(width, height, Channels::Rgb, bytes, OPAQUE_ALPHA)
+    }
+
+    fn from_channel_bytes(
+        width: usize,
+        height: usize,
+        channels: Channels,
+        data: &[u8],
+        alpha: u8,
+    ) -> Result<Self, FromBytesError> {
+        let expected = width
+            .checked_mul(height)
+            .and_then(|size| size.checked_mul(channels.count()))
+            .ok_or(FromBytesError::DimensionOverflow)?;
+        if data.len() != expected {
+            return Err(FromBytesError::LengthMismatch {
+                expected,
+                actual: data.len(),
+            });
         }
+
+        let pixels: Box<[Pixel]> = data
+            .chunks_exact(channels.count())
+            .map(|chunk| {
+                let rgba = match channels {
+                    Channels::Gray => Rgba {
+                        r: chunk[0],
+                        g: chunk[0],
+                        b: chunk[0],
+                        a: alpha,
+                    },
+                    Channels::Rgb => Rgba {
+                        r: chunk[0],
+                        g: chunk[1],
+                        b: chunk[2],
+                        a: alpha,
+                    },
+                    Channels::Rgba => Rgba {
+                        r: chunk[0],
+                        g: chunk[1],
+                        b: chunk[2],
+                        a: chunk[3],
+                    },
+                };
+                Pixel::from(rgba)
+            })
+            .collect();
+
+        Ok(Self::new(width, height, pixels))
+    }
+
+    pub fn enumerate_pixels(&self) -> impl Iterator<Item = (usize, usize, &Pixel)> {
+        ImageRead::enumerate_pixels(self)
+    }
+
+    /// The rows of pixels from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[Pixel]> {
+        ImageRead::rows(self)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &Pixel>> {
+        ImageRead::columns(self)
+    }
+
+    pub fn enumerate_pixels_mut(&mut self) -> impl Iterator<Item = (usize, usize, &mut Pixel)> {
+        let width = self.width;
+        self.data
+            .iter_mut()
+            .enumerate()
+            .map(move |(i, pixel)| (i % width, i / width, pixel))
+    }
+
+    pub fn map_pixels<F: FnMut(Rgba) -> Rgba>(&self, mut f: F) -> Image {
+        Self {
+            width: self.width,
+            height: self.height,
+            data: self
+                .data
+                .iter()
+                .map(|pixel| Pixel::from(f(pixel.rgba())))
+                .collect(),
+        }
+    }
+
+    pub fn map_pixels_mut<F: FnMut(Rgba) -> Rgba>(&mut self, mut f: F) {
+        for pixel in self.data.iter_mut() {
+            let rgba = pixel.rgba_mut();
+            *rgba = f(*rgba);
+        }
+    }
+
+    /// Calls `f` with the index and the pixels of each row, from top to bottom
+    pub fn map_rows<F: Fn(usize, &mut [Pixel])>(&mut self, f: F) {
+        if self.width == 0 {
+            (0..self.height).for_each(|y| f(y, &mut []));
+            return;
+        }
+        for (y, row) in self.data.chunks_exact_mut(self.width).enumerate() {
+            f(y, row);
+        }
+    }
+
+    /// Pixel data as bytes in `r, g, b, a` order, without copying
+    pub fn as_rgba_bytes(&self) -> &[u8] {
+        const _: () = assert!(core::mem::size_of::<Pixel>() == 4);
+
+        // SAFETY: Pixel is a #[repr(C)] union whose fields are both 4 bytes wide
+        // and have no padding, so the slice is made of fully initialized bytes,
+        // laid out as the #[repr(C)] Rgba fields: r, g, b, a. u8 has no
+        // alignment requirement and the length covers exactly the same memory.
+        unsafe {
+            core::slice::from_raw_parts(
+                self.data.as_ptr().cast::<u8>(),
+                self.data.len() * core::mem::size_of::<Pixel>(),
+            )
+        }
+    }
+
+    pub fn matches(&self, width: usize, height: usize, data: &[Pixel]) -> bool {
+        self.width == width && self.height == height && *self.data == *data
+    }
+
+    /// Compares the dimensions and the red, green and blue channels only
+    pub fn eq_ignore_alpha(&self, other: &Image) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.data.iter().zip(other.data.iter()).all(|(a, b)| {
+                let (a, b) = (a.rgba(), b.rgba());
+                (a.r, a.g, a.b) == (b.r, b.g, b.b)
+            })
+    }
+
+    /// Whether every pixel has an alpha of 255, an empty image is opaque
+    pub fn is_opaque(&self) -> bool {
+        self.data.iter().all(|pixel| pixel.a() == OPAQUE_ALPHA)
     }
 
     pub fn width(&self) -> usize {
@@ -56,6 +288,213 @@ impl Image {
     }
 }
 
+/// Opaque black, so that cleared images are visible
+impl Default for Rgba {
+    fn default() -> Self {
+        Self::BLACK
+    }
+}
+
+/// ```
+/// use image_parser::Rgba;
+///
+/// assert_eq!(Rgba::RED, Rgba::rgb(255, 0, 0));
+/// assert_eq!(Rgba::WHITE.a, 255);
+/// assert_eq!(Rgba::TRANSPARENT, Rgba::rgba(0, 0, 0, 0));
+/// ```
+impl Rgba {
+    pub const BLACK: Rgba = Rgba::rgb(0, 0, 0);
+    pub const WHITE: Rgba = Rgba::rgb(255, 255, 255);
+    pub const TRANSPARENT: Rgba = Rgba::rgba(0, 0, 0, 0);
+    pub const RED: Rgba = Rgba::rgb(255, 0, 0);
+    pub const GREEN: Rgba = Rgba::rgb(0, 255, 0);
+    pub const BLUE: Rgba = Rgba::rgb(0, 0, 255);
+
+    /// Opaque color, like the pixels of the parser that use
+    /// [`DEFAULT_ALPHA_VALUE`](crate::DEFAULT_ALPHA_VALUE)
+    ///
+    /// ```
+    /// use image_parser::Rgba;
+    ///
+    /// assert_eq!(Rgba::rgb(1, 2, 3), Rgba { r: 1, g: 2, b: 3, a: 255 });
+    /// ```
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Rgba {
+        Rgba {
+            r,
+            g,
+            b,
+            a: OPAQUE_ALPHA,
+        }
+    }
+
+    #[allow(clippy::self_named_constructors)]
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Rgba {
+        Rgba { r, g, b, a }
+    }
+
+    pub fn channel(&self, channel: Channel) -> u8 {
+        match channel {
+            Channel::Red => self.r,
+            Channel::Green => self.g,
+            Channel::Blue => self.b,
+            Channel::Alpha => self.a,
+        }
+    }
+
+    /// Rec. 601 luma of the color, alpha is ignored
+    pub fn luminance(&self) -> u8 {
+        ((self.r as u32 * 299 + self.g as u32 * 587 + self.b as u32 * 114 + 500) / 1000) as u8
+    }
+
+    /// Per channel addition clamped to 255, alpha included
+    pub fn saturating_add(self, other: Rgba) -> Rgba {
+        self.zip_with(other, u8::saturating_add)
+    }
+
+    /// Per channel subtraction clamped to 0, alpha included
+    pub fn saturating_sub(self, other: Rgba) -> Rgba {
+        self.zip_with(other, u8::saturating_sub)
+    }
+
+    /// Multiplies every channel, alpha included, by `factor`, rounding and
+    /// clamping the results to 0..=255
+    pub fn scale(self, factor: f32) -> Rgba {
+        let scale = |channel: u8| (channel as f32 * factor + 0.5).clamp(0., 255.) as u8;
+        Rgba::rgba(scale(self.r), scale(self.g), scale(self.b), scale(self.a))
+    }
+
+    /// Parses `RRGGBB` or `RRGGBBAA` hexadecimal digits, optionally prefixed
+    /// by `#`, the color is opaque without the alpha digits
+    ///
+    /// ```
+    /// use image_parser::Rgba;
+    ///
+    /// let orange = Rgba::from_hex("#ff8800ff").unwrap();
+    /// assert_eq!(orange, Rgba::rgb(255, 136, 0));
+    /// assert_eq!(Rgba::from_hex("FF8800"), Ok(orange));
+    /// assert_eq!(orange.to_hex(), "#ff8800ff");
+    /// ```
+    pub fn from_hex(hex: &str) -> Result<Rgba, HexError> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if let Some(digit) = digits.chars().find(|digit| !digit.is_ascii_hexdigit()) {
+            return Err(HexError::InvalidDigit(digit));
+        }
+        if !matches!(digits.len(), 6 | 8) {
+            return Err(HexError::InvalidLength(digits.len()));
+        }
+
+        let channel = |index: usize| {
+            u8::from_str_radix(&digits[index * 2..index * 2 + 2], 16)
+                .expect("the digits were checked")
+        };
+        let a = if digits.len() == 8 { channel(3) } else { 255 };
+        Ok(Rgba::rgba(channel(0), channel(1), channel(2), a))
+    }
+
+    /// Lowercase `#RRGGBBAA`
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+    }
+
+    fn zip_with(self, other: Rgba, f: fn(u8, u8) -> u8) -> Rgba {
+        Rgba::rgba(
+            f(self.r, other.r),
+            f(self.g, other.g),
+            f(self.b, other.b),
+            f(self.a, other.a),
+        )
+    }
+}
+
+impl Channels {
+    pub fn count(&self) -> usize {
+        match self {
+            Channels::Gray => 1,
+            Channels::Rgb => 3,
+            Channels::Rgba => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageNewError {
+    DimensionOverflow,
+    DataLengthMismatch { expected: usize, actual: usize },
+    FailedToAllocate(TryReserveError),
+}
+
+impl Display for ImageNewError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ImageNewError::DimensionOverflow => write!(f, "width * height overflowed"),
+            ImageNewError::DataLengthMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "expected width * height = {expected} pixels, found {actual}"
+                )
+            }
+            ImageNewError::FailedToAllocate(err) => {
+                write!(f, "failed to allocate image data: {err}")
+            }
+        }
+    }
+}
+
+impl Error for ImageNewError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImageNewError::FailedToAllocate(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FromBytesError {
+    DimensionOverflow,
+    LengthMismatch { expected: usize, actual: usize },
+}
+
+impl Display for FromBytesError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FromBytesError::DimensionOverflow => {
+                write!(f, "width * height * channel count overflows usize")
+            }
+            FromBytesError::LengthMismatch { expected, actual } => {
+                write!(f, "expected {expected} bytes of pixel data, found {actual}")
+            }
+        }
+    }
+}
+
+impl Error for FromBytesError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HexError {
+    InvalidLength(usize),
+    InvalidDigit(char),
+}
+
+impl Display for HexError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            HexError::InvalidLength(len) => {
+                write!(f, "expected 6 or 8 hexadecimal digits, found {len}")
+            }
+            HexError::InvalidDigit(digit) => write!(f, "{digit:?} is not a hexadecimal digit"),
+        }
+    }
+}
+
+impl Error for HexError {}
+
+impl PartialEq<(usize, usize, &[Pixel])> for Image {
+    fn eq(&self, (width, height, data): &(usize, usize, &[Pixel])) -> bool {
+        self.matches(*width, *height, data)
+    }
+}
+
 impl Deref for Image {
     type Target = [Pixel];
 
@@ -84,22 +523,112 @@ impl IndexMut<usize> for Image {
     }
 }
 
+impl IntoIterator for Image {
+    type Item = Pixel;
+    type IntoIter = alloc::vec::IntoIter<Pixel>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_vec().into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Image {
+    type Item = &'a Pixel;
+    type IntoIter = core::slice::Iter<'a, Pixel>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter()
+    }
+}
+
+// Both fields of the union are plain integers of the same size (four u8 vs one
+// u32), so every bit pattern is a valid value for either of them. Reading one
+// field after writing the other is therefore always sound, which is what the
+// unsafe blocks below rely on.
 impl Pixel {
+    /// Raw view of the pixel's memory as a u32, the value depends on the host
+    /// endianness. Use [`Pixel::to_rgba_u32`] for a platform independent value.
     pub fn color(&self) -> u32 {
+        // SAFETY: any 4 bytes are a valid u32
         unsafe { self.color }
     }
 
     pub fn rgba(&self) -> Rgba {
+        // SAFETY: any 4 bytes are a valid Rgba
         unsafe { self.rgba }
     }
 
     pub fn color_mut(&mut self) -> &mut u32 {
+        // SAFETY: any 4 bytes are a valid u32, and any u32 written through the
+        // reference is a valid Rgba
         unsafe { &mut self.color }
     }
 
     pub fn rgba_mut(&mut self) -> &mut Rgba {
+        // SAFETY: any 4 bytes are a valid Rgba, and any Rgba written through the
+        // reference is a valid u32
         unsafe { &mut self.rgba }
     }
+
+    pub const fn from_rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self {
+            rgba: Rgba { r, g, b, a },
+        }
+    }
+
+    /// Same as `Pixel::from(color)`, the channels depend on the host endianness
+    /// like [`Pixel::color`]
+    pub const fn from_color(color: u32) -> Self {
+        Self { color }
+    }
+
+    pub fn r(&self) -> u8 {
+        self.rgba().r
+    }
+
+    pub fn g(&self) -> u8 {
+        self.rgba().g
+    }
+
+    pub fn b(&self) -> u8 {
+        self.rgba().b
+    }
+
+    pub fn a(&self) -> u8 {
+        self.rgba().a
+    }
+
+    pub fn set_r(&mut self, value: u8) {
+        self.rgba_mut().r = value;
+    }
+
+    pub fn set_g(&mut self, value: u8) {
+        self.rgba_mut().g = value;
+    }
+
+    pub fn set_b(&mut self, value: u8) {
+        self.rgba_mut().b = value;
+    }
+
+    pub fn set_a(&mut self, value: u8) {
+        self.rgba_mut().a = value;
+    }
+
+    /// Packs the channels as `0xRRGGBBAA` regardless of the host endianness
+    pub fn to_rgba_u32(&self) -> u32 {
+        u32::from_be_bytes(self.as_bytes())
+    }
+
+    /// Returns the channels in `[r, g, b, a]` order
+    pub fn as_bytes(&self) -> [u8; 4] {
+        let Rgba { r, g, b, a } = self.rgba();
+        [r, g, b, a]
+    }
+
+    /// Builds a pixel from channels in `[r, g, b, a]` order
+    pub const fn from_bytes([r, g, b, a]: [u8; 4]) -> Self {
+        Self::from_rgba(r, g, b, a)
+    }
 }
 
 impl PartialEq for Pixel {
@@ -108,15 +637,84 @@ impl PartialEq for Pixel {
     }
 }
 
+/// Opaque black, see [`Rgba::default`]
+impl Default for Pixel {
+    fn default() -> Self {
+        Self {
+            rgba: Rgba::default(),
+        }
+    }
+}
+
+// Serialized through the Rgba view so the output doesn't depend on endianness
+#[cfg(feature = "serde")]
+impl serde::Serialize for Pixel {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.rgba().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Pixel {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Pixel::from(Rgba::deserialize(deserializer)?))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Image {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Image", 3)?;
+        state.serialize_field("width", &self.width)?;
+        state.serialize_field("height", &self.height)?;
+        state.serialize_field("data", &self.data)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Image {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "Image")]
+        struct RawImage {
+            width: usize,
+            height: usize,
+            data: Vec<Pixel>,
+        }
+
+        let raw = RawImage::deserialize(deserializer)?;
+        Image::try_new(raw.width, raw.height, raw.data).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl Image {
+    /// Copy of the channels with a `(height, width, 4)` shape, indexed by
+    /// `[y, x, channel]` with the channels in `r, g, b, a` order
+    pub fn to_ndarray(&self) -> ndarray::Array3<u8> {
+        ndarray::Array3::from_shape_vec((self.height, self.width, 4), self.as_rgba_bytes().to_vec())
+            .expect("an image holds width * height pixels of 4 channels")
+    }
+}
+
+impl Hash for Pixel {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.color().hash(state);
+    }
+}
+
 impl Debug for Pixel {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "Pixel {{ color: {} }}", self.color())
     }
 }
 
 impl From<u32> for Pixel {
     fn from(color: u32) -> Self {
-        Self { color }
+        Self::from_color(color)
     }
 }
 
@@ -125,3 +723,426 @@ impl From<Rgba> for Pixel {
         Self { rgba }
     }
 }
+
+/// Pixel shorthand for the tests of every module
+#[cfg(test)]
+pub(crate) fn rgba(r: u8, g: u8, b: u8, a: u8) -> Pixel {
+    Pixel::from(Rgba::rgba(r, g, b, a))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pixel_bytes() {
+        let pixel = Pixel::from_bytes([1, 2, 3, 4]);
+        assert_eq!(
+            pixel.rgba(),
+            Rgba {
+                r: 1,
+                g: 2,
+                b: 3,
+                a: 4
+            }
+        );
+        assert_eq!(pixel.as_bytes(), [1, 2, 3, 4]);
+        assert_eq!(rgba(250, 0, 7, 128).as_bytes(), [250, 0, 7, 128]);
+    }
+
+    #[test]
+    fn rgba_u32() {
+        assert_eq!(rgba(0x12, 0x34, 0x56, 0x78).to_rgba_u32(), 0x12345678);
+        assert_eq!(rgba(255, 0, 0, 0).to_rgba_u32(), 0xff000000);
+    }
+
+    #[test]
+    fn hash_set_deduplicates() {
+        let mut set = std::collections::HashSet::new();
+        set.insert(Image::new(2, 1, [rgba(1, 2, 3, 4), rgba(5, 6, 7, 8)]));
+        set.insert(Image::new(2, 1, [rgba(1, 2, 3, 4), rgba(5, 6, 7, 8)]));
+        assert_eq!(set.len(), 1);
+
+        set.insert(Image::new(1, 2, [rgba(1, 2, 3, 4), rgba(5, 6, 7, 8)]));
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn enumerate_pixels_mut() {
+        let mut image = Image::new(3, 2, [rgba(0, 0, 0, 0); 6]);
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            pixel.rgba_mut().b = x as u8;
+            pixel.rgba_mut().g = y as u8;
+        }
+
+        let expected = Image::new(
+            3,
+            2,
+            [
+                rgba(0, 0, 0, 0),
+                rgba(0, 0, 1, 0),
+                rgba(0, 0, 2, 0),
+                rgba(0, 1, 0, 0),
+                rgba(0, 1, 1, 0),
+                rgba(0, 1, 2, 0),
+            ],
+        );
+        assert_eq!(expected, image);
+
+        let coords: Vec<_> = image.enumerate_pixels().map(|(x, y, _)| (x, y)).collect();
+        assert_eq!(coords, [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn default_is_opaque_black() {
+        assert_eq!(
+            Rgba::default(),
+            Rgba {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255
+            }
+        );
+        assert_eq!(Pixel::default(), rgba(0, 0, 0, 255));
+    }
+
+    #[test]
+    fn filled() {
+        let image = Image::filled(2, 3, rgba(1, 2, 3, 4)).unwrap();
+        assert_eq!(Image::new(2, 3, [rgba(1, 2, 3, 4); 6]), image);
+
+        let res = Image::filled(usize::MAX, 2, rgba(1, 2, 3, 4)).unwrap_err();
+        assert_eq!(res, ImageNewError::DimensionOverflow);
+
+        let res = Image::filled(usize::MAX, 1, rgba(1, 2, 3, 4)).unwrap_err();
+        match res {
+            ImageNewError::FailedToAllocate(_) => {}
+            _ => panic!("Expected ImageNewError::FailedToAllocate found {res}"),
+        };
+    }
+
+    #[test]
+    fn map_pixels() {
+        let invert = |rgba: Rgba| Rgba {
+            r: 255 - rgba.r,
+            g: 255 - rgba.g,
+            b: 255 - rgba.b,
+            a: 255 - rgba.a,
+        };
+        let mut image = Image::new(2, 1, [rgba(0, 10, 20, 30), rgba(255, 128, 1, 0)]);
+        let expected = Image::new(2, 1, [rgba(255, 245, 235, 225), rgba(0, 127, 254, 255)]);
+        assert_eq!(expected, image.map_pixels(invert));
+
+        image.map_pixels_mut(invert);
+        assert_eq!(expected, image);
+    }
+
+    #[test]
+    fn map_rows() {
+        let mut image = Image::new(2, 3, [rgba(0, 1, 2, 3); 6]);
+        image.map_rows(|y, row| {
+            assert_eq!(row.len(), 2);
+            row.iter_mut().for_each(|pixel| pixel.set_r(y as u8));
+        });
+        let reds: Vec<u8> = image.iter().map(|pixel| pixel.r()).collect();
+        assert_eq!(reds, [0, 0, 1, 1, 2, 2]);
+
+        let rows = core::cell::Cell::new(0);
+        Image::new(0, 3, []).map_rows(|y, row| {
+            assert!(row.is_empty());
+            assert_eq!(y, rows.get());
+            rows.set(y + 1);
+        });
+        assert_eq!(rows.get(), 3);
+    }
+
+    #[test]
+    fn as_rgba_bytes() {
+        let image = Image::new(2, 1, [rgba(1, 2, 3, 4), rgba(5, 6, 7, 8)]);
+        assert_eq!(image.as_rgba_bytes(), [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(
+            Image::from_rgba_bytes(2, 1, image.as_rgba_bytes()).unwrap(),
+            image
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ndarray")]
+    fn to_ndarray() {
+        let image = Image::new(
+            2,
+            3,
+            (0..6)
+                .map(|i| rgba(i, 10 + i, 20 + i, 30 + i))
+                .collect::<Vec<_>>(),
+        );
+        let array = image.to_ndarray();
+        assert_eq!(array.shape(), [3, 2, 4]);
+        assert_eq!(array[[0, 0, 0]], image[0].r());
+        assert_eq!(array[[2, 1, 0]], 5);
+        assert_eq!(array[[1, 0, 3]], 32);
+        assert_eq!(Image::new(0, 3, []).to_ndarray().shape(), [3, 0, 4]);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let image = Image::new(2, 1, [rgba(1, 2, 3, 4), rgba(5, 6, 7, 8)]);
+        let json = serde_json::to_string(&image).unwrap();
+        assert_eq!(
+            json,
+            r#"{"width":2,"height":1,"data":[{"r":1,"g":2,"b":3,"a":4},{"r":5,"g":6,"b":7,"a":8}]}"#
+        );
+        assert_eq!(serde_json::from_str::<Image>(&json).unwrap(), image);
+
+        let res = serde_json::from_str::<Image>(r#"{"width":2,"height":2,"data":[]}"#);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn matches() {
+        let data = [rgba(1, 2, 3, 4), rgba(5, 6, 7, 8)];
+        let image = Image::new(2, 1, data);
+        assert!(image.matches(2, 1, &data));
+        assert!(!image.matches(1, 2, &data));
+        assert!(!image.matches(2, 1, &[data[1], data[0]]));
+        assert_eq!(image, (2, 1, &data[..]));
+        assert_ne!(image, (1, 2, &data[..]));
+    }
+
+    #[test]
+    fn raw_round_trip() {
+        let image = Image::new(2, 1, [rgba(1, 2, 3, 4), rgba(5, 6, 7, 8)]);
+        let (width, height, data) = image.clone().into_raw();
+        let pointer = data.as_ptr();
+        let res = Image::from_raw(width, height, data).unwrap();
+        assert_eq!(res, image);
+        assert_eq!(res.as_ptr(), pointer);
+
+        let res = Image::from_raw(3, 1, Box::new([rgba(1, 2, 3, 4)])).unwrap_err();
+        assert_eq!(
+            res,
+            ImageNewError::DataLengthMismatch {
+                expected: 3,
+                actual: 1
+            }
+        );
+    }
+
+    #[test]
+    fn try_new() {
+        let image = Image::try_new(2, 1, [rgba(1, 2, 3, 4), rgba(5, 6, 7, 8)]).unwrap();
+        assert_eq!(image.width(), 2);
+        assert_eq!(image.height(), 1);
+
+        let res = Image::try_new(2, 2, [rgba(1, 2, 3, 4)]).unwrap_err();
+        assert_eq!(
+            res,
+            ImageNewError::DataLengthMismatch {
+                expected: 4,
+                actual: 1
+            }
+        );
+
+        let res = Image::try_new(usize::MAX, 2, []).unwrap_err();
+        assert_eq!(res, ImageNewError::DimensionOverflow);
+    }
+
+    #[test]
+    #[should_panic(expected = "Image::new() width * height overflowed")]
+    fn new_overflow_panics() {
+        Image::new(usize::MAX, 2, []);
+    }
+
+    #[test]
+    #[should_panic(expected = "Image::new() expected width * height = 4 pixels, found 3")]
+    fn new_length_mismatch_panics() {
+        Image::new(2, 2, [Pixel::from(0); 3]);
+    }
+
+    #[test]
+    fn from_rgba_and_rgb_bytes() {
+        let image = Image::from_rgba_bytes(1, 1, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(Image::new(1, 1, [rgba(1, 2, 3, 4)]), image);
+
+        let res = Image::from_rgba_bytes(1, 1, &[1, 2, 3]).unwrap_err();
+        assert_eq!(
+            res,
+            FromBytesError::LengthMismatch {
+                expected: 4,
+                actual: 3
+            }
+        );
+
+        let image = Image::from_rgb_bytes(1, 1, &[1, 2, 3]).unwrap();
+        assert_eq!(Image::new(1, 1, [rgba(1, 2, 3, 255)]), image);
+
+        let res = Image::from_rgb_bytes(1, 1, &[1, 2, 3, 4]).unwrap_err();
+        assert_eq!(
+            res,
+            FromBytesError::LengthMismatch {
+                expected: 3,
+                actual: 4
+            }
+        );
+    }
+
+    #[test]
+    fn from_raw_bytes_gray() {
+        let image = Image::from_raw_bytes(2, 1, Channels::Gray, &[12, 200]).unwrap();
+        let expected = Image::new(2, 1, [rgba(12, 12, 12, 255), rgba(200, 200, 200, 255)]);
+        assert_eq!(expected, image);
+        assert_eq!(crate::GrayImage::new(2, 1, [12, 200]).to_rgba(), image);
+
+        let res = Image::from_raw_bytes(2, 1, Channels::Gray, &[12, 200, 3]).unwrap_err();
+        assert_eq!(
+            res,
+            FromBytesError::LengthMismatch {
+                expected: 2,
+                actual: 3
+            }
+        );
+    }
+
+    #[test]
+    fn from_raw_bytes_rgb() {
+        let image = Image::from_raw_bytes(1, 2, Channels::Rgb, &[1, 2, 3, 4, 5, 6]).unwrap();
+        let expected = Image::new(1, 2, [rgba(1, 2, 3, 255), rgba(4, 5, 6, 255)]);
+        assert_eq!(expected, image);
+        assert_eq!(
+            Image::from_rgb_bytes(1, 2, &[1, 2, 3, 4, 5, 6]).unwrap(),
+            image
+        );
+
+        let res = Image::from_raw_bytes(1, 2, Channels::Rgb, &[1, 2, 3, 4, 5]).unwrap_err();
+        assert_eq!(
+            res,
+            FromBytesError::LengthMismatch {
+                expected: 6,
+                actual: 5
+            }
+        );
+    }
+
+    #[test]
+    fn from_raw_bytes_rgba() {
+        let image = Image::from_raw_bytes(2, 1, Channels::Rgba, &[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        let expected = Image::new(2, 1, [rgba(1, 2, 3, 4), rgba(5, 6, 7, 8)]);
+        assert_eq!(expected, image);
+
+        let res = Image::from_raw_bytes(2, 1, Channels::Rgba, &[1, 2, 3, 4]).unwrap_err();
+        assert_eq!(
+            res,
+            FromBytesError::LengthMismatch {
+                expected: 8,
+                actual: 4
+            }
+        );
+
+        let res = Image::from_raw_bytes(usize::MAX, 2, Channels::Rgba, &[]).unwrap_err();
+        assert_eq!(res, FromBytesError::DimensionOverflow);
+    }
+
+    #[test]
+    fn into_iterator() {
+        let image = Image::new(3, 1, [Pixel::from(1), Pixel::from(20), Pixel::from(300)]);
+
+        let mut sum = 0;
+        for pixel in &image {
+            sum += pixel.color();
+        }
+        assert_eq!(sum, 321);
+
+        let mut sum = 0;
+        for pixel in image.clone() {
+            sum += pixel.color();
+        }
+        assert_eq!(sum, 321);
+
+        let colors: Vec<u32> = image.into_iter().map(|pixel| pixel.color() * 2).collect();
+        assert_eq!(colors, [2, 40, 600]);
+    }
+
+    #[test]
+    fn eq_ignore_alpha() {
+        let image = Image::new(2, 1, [rgba(1, 2, 3, 0), rgba(4, 5, 6, 7)]);
+        let opaque = Image::new(2, 1, [rgba(1, 2, 3, 255), rgba(4, 5, 6, 255)]);
+        assert_ne!(image, opaque);
+        assert!(image.eq_ignore_alpha(&opaque));
+
+        let different = Image::new(2, 1, [rgba(1, 2, 3, 0), rgba(4, 5, 7, 7)]);
+        assert!(!image.eq_ignore_alpha(&different));
+        let transposed = Image::new(1, 2, [rgba(1, 2, 3, 0), rgba(4, 5, 6, 7)]);
+        assert!(!image.eq_ignore_alpha(&transposed));
+    }
+
+    #[test]
+    fn is_opaque() {
+        let opaque = Image::new(2, 1, [rgba(1, 2, 3, 255), rgba(4, 5, 6, 255)]);
+        assert!(opaque.is_opaque());
+        let mixed = Image::new(2, 1, [rgba(1, 2, 3, 255), rgba(4, 5, 6, 254)]);
+        assert!(!mixed.is_opaque());
+        assert!(Image::new(0, 0, []).is_opaque());
+    }
+
+    #[test]
+    fn channel_accessors() {
+        let mut pixel = rgba(1, 2, 3, 4);
+        assert_eq!((pixel.r(), pixel.g(), pixel.b(), pixel.a()), (1, 2, 3, 4));
+
+        pixel.set_r(10);
+        assert_eq!(pixel.as_bytes(), [10, 2, 3, 4]);
+        pixel.set_g(20);
+        assert_eq!(pixel.as_bytes(), [10, 20, 3, 4]);
+        pixel.set_b(30);
+        assert_eq!(pixel.as_bytes(), [10, 20, 30, 4]);
+        pixel.set_a(40);
+        assert_eq!(pixel.as_bytes(), [10, 20, 30, 40]);
+        assert_eq!(
+            (pixel.r(), pixel.g(), pixel.b(), pixel.a()),
+            (10, 20, 30, 40)
+        );
+    }
+
+    #[test]
+    fn const_pixels() {
+        const PALETTE: [Pixel; 4] = [
+            Pixel::from_rgba(0, 0, 0, 255),
+            Pixel::from_rgba(255, 0, 0, 255),
+            Pixel::from_bytes([0, 255, 0, 128]),
+            Pixel::from_color(u32::MAX),
+        ];
+        assert_eq!(PALETTE[0].rgba(), Rgba::BLACK);
+        assert_eq!(PALETTE[1].rgba(), Rgba::RED);
+        assert_eq!(PALETTE[2].as_bytes(), [0, 255, 0, 128]);
+        assert_eq!(PALETTE[3].as_bytes(), [255; 4]);
+        assert_eq!(Pixel::from_color(42), Pixel::from(42));
+    }
+
+    #[test]
+    fn rgba_arithmetic() {
+        let color = Rgba::rgba(200, 100, 0, 255);
+        assert_eq!(
+            color.saturating_add(Rgba::rgba(100, 100, 100, 1)),
+            Rgba::rgba(255, 200, 100, 255)
+        );
+        assert_eq!(
+            color.saturating_sub(Rgba::rgba(100, 150, 1, 55)),
+            Rgba::rgba(100, 0, 0, 200)
+        );
+        assert_eq!(color.scale(0.5), Rgba::rgba(100, 50, 0, 128));
+        assert_eq!(color.scale(2.), Rgba::rgba(255, 200, 0, 255));
+        assert_eq!(color.scale(-1.), Rgba::TRANSPARENT);
+    }
+
+    #[test]
+    fn rgba_hex() {
+        assert_eq!(Rgba::from_hex("#0a0B0c0D"), Ok(Rgba::rgba(10, 11, 12, 13)));
+        assert_eq!(Rgba::rgba(10, 11, 12, 13).to_hex(), "#0a0b0c0d");
+        assert_eq!(Rgba::from_hex("#fff"), Err(HexError::InvalidLength(3)));
+        assert_eq!(Rgba::from_hex("##ffffff"), Err(HexError::InvalidDigit('#')));
+        assert_eq!(Rgba::from_hex("ff00gg"), Err(HexError::InvalidDigit('g')));
+        assert_eq!(Rgba::from_hex(""), Err(HexError::InvalidLength(0)));
+    }
+}
@@ -0,0 +1,119 @@
+use alloc::boxed::Box;
+use core::ops::{Deref, Index};
+
+use crate::ImageNewError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Rgb16 {
+    pub r: u16,
+    pub g: u16,
+    pub b: u16,
+}
+
+/// Image holding the samples exactly as they are stored in the file, relative
+/// to `maxval` instead of being rescaled to u8 like [`Image`](crate::Image)
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Image16 {
+    data: Box<[Rgb16]>,
+
+    width: usize,
+    height: usize,
+    maxval: u16,
+}
+
+impl Image16 {
+    /// # Panics
+    ///
+    /// Same conditions as [`Image::new`](crate::Image::new), see
+    /// [`Image16::try_new`]. The panic message is `Image16::new() ` followed by
+    /// the error.
+    pub fn new(width: usize, height: usize, maxval: u16, data: impl Into<Box<[Rgb16]>>) -> Self {
+        Self::try_new(width, height, maxval, data)
+            .unwrap_or_else(|err| panic!("Image16::new() {err}"))
+    }
+
+    pub fn try_new(
+        width: usize,
+        height: usize,
+        maxval: u16,
+        data: impl Into<Box<[Rgb16]>>,
+    ) -> Result<Self, ImageNewError> {
+        let data = data.into();
+
+        let expected = width
+            .checked_mul(height)
+            .ok_or(ImageNewError::DimensionOverflow)?;
+        if expected != data.len() {
+            return Err(ImageNewError::DataLengthMismatch {
+                expected,
+                actual: data.len(),
+            });
+        }
+
+        Ok(Self {
+            data,
+            width,
+            height,
+            maxval,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn maxval(&self) -> u16 {
+        self.maxval
+    }
+}
+
+impl Deref for Image16 {
+    type Target = [Rgb16];
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl Index<usize> for Image16 {
+    type Output = Rgb16;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn try_new() {
+        let image = Image16::try_new(2, 1, 1000, [Rgb16::default(); 2]).unwrap();
+        assert_eq!(
+            (image.width(), image.height(), image.maxval()),
+            (2, 1, 1000)
+        );
+        assert_eq!(
+            Image16::try_new(2, 2, 1000, [Rgb16::default(); 3]),
+            Err(ImageNewError::DataLengthMismatch {
+                expected: 4,
+                actual: 3
+            })
+        );
+        assert_eq!(
+            Image16::try_new(usize::MAX, 2, 1000, []),
+            Err(ImageNewError::DimensionOverflow)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Image16::new() expected width * height = 4 pixels, found 3")]
+    fn new_length_mismatch_panics() {
+        Image16::new(2, 2, 255, [Rgb16::default(); 3]);
+    }
+}
@@ -1,4 +1,34 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+mod analysis;
+mod ansi;
+mod bmp;
+mod builder;
+mod color;
+mod concat;
+mod diff;
+mod draw;
+mod filters;
+mod gray;
 mod image;
+mod image16;
+mod palette;
+mod png;
 pub mod ppm;
+mod resize;
+mod view;
 
-pub use image::Image;
+pub use builder::{BuildError, ImageBuilder};
+pub use concat::{ConcatError, TileError};
+pub use diff::{DiffStats, DimensionMismatch};
+pub use draw::OutOfBounds;
+pub use gray::GrayImage;
+pub use image::{
+    Channel, Channels, FromBytesError, HexError, Image, ImageNewError, Pixel, Rgba,
+    DEFAULT_ALPHA_VALUE,
+};
+pub use image16::{Image16, Rgb16};
+pub use palette::IndexedError;
+pub use view::{ImageRead, ImageView, ImageWindow};
@@ -0,0 +1,338 @@
+use alloc::{vec, vec::Vec};
+use core::{error::Error, fmt::Display};
+
+use crate::{Image, Pixel, Rgba};
+
+impl Image {
+    /// Maps each index of the row major `indices` to its color in `palette`
+    pub fn from_indexed(
+        width: usize,
+        height: usize,
+        indices: &[u8],
+        palette: &[Rgba],
+    ) -> Result<Image, IndexedError> {
+        let expected = width
+            .checked_mul(height)
+            .ok_or(IndexedError::DimensionOverflow)?;
+        if indices.len() != expected {
+            return Err(IndexedError::LengthMismatch {
+                expected,
+                actual: indices.len(),
+            });
+        }
+
+        let pixels = indices
+            .iter()
+            .map(|&index| match palette.get(index as usize) {
+                Some(&color) => Ok(Pixel::from(color)),
+                None => Err(IndexedError::IndexOutOfPalette {
+                    index,
+                    palette_len: palette.len(),
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Image::new(width, height, pixels))
+    }
+
+    /// Index of the nearest `palette` color of each pixel, by squared euclidean
+    /// distance over red, green and blue, alpha is ignored. The first color
+    /// wins the ties.
+    ///
+    /// # Panics
+    ///
+    /// When the palette is empty or has more than 256 colors.
+    pub fn quantize_to_palette(&self, palette: &[Rgba]) -> Vec<u8> {
+        assert!(
+            !palette.is_empty() && palette.len() <= 256,
+            "Image::quantize_to_palette() needs between 1 and 256 colors, got {}",
+            palette.len()
+        );
+        self.iter()
+            .map(|pixel| nearest_color(palette, pixel.rgba()) as u8)
+            .collect()
+    }
+
+    /// Median cut palette of at most `max_colors` opaque colors, alpha is
+    /// ignored. The boxes of colors are split along their longest axis until
+    /// there are `max_colors` of them or they all hold a single color.
+    pub fn generate_palette(&self, max_colors: usize) -> Vec<Rgba> {
+        if max_colors == 0 || self.is_empty() {
+            return Vec::new();
+        }
+
+        let colors: Vec<[u8; 3]> = self
+            .iter()
+            .map(|pixel| {
+                let rgba = pixel.rgba();
+                [rgba.r, rgba.g, rgba.b]
+            })
+            .collect();
+        let mut boxes = vec![colors];
+        while boxes.len() < max_colors {
+            let Some((index, axis, _)) = boxes
+                .iter()
+                .enumerate()
+                .map(|(index, colors)| {
+                    let (axis, range) = longest_axis(colors);
+                    (index, axis, range)
+                })
+                .filter(|&(_, _, range)| range > 0)
+                .max_by_key(|&(_, _, range)| range)
+            else {
+                break;
+            };
+
+            let colors = &mut boxes[index];
+            colors.sort_unstable_by_key(|color| color[axis]);
+            // Splits next to the median without separating equal values, the
+            // range being positive there is always a value on each side
+            let median = colors[colors.len() / 2][axis];
+            let mut split = colors.partition_point(|color| color[axis] < median);
+            if split == 0 {
+                split = colors.partition_point(|color| color[axis] <= median);
+            }
+            let upper = colors.split_off(split);
+            boxes.push(upper);
+        }
+
+        boxes
+            .iter()
+            .map(|colors| {
+                let count = colors.len() as u64;
+                let average = |channel: usize| {
+                    let sum: u64 = colors.iter().map(|color| color[channel] as u64).sum();
+                    ((sum + count / 2) / count) as u8
+                };
+                Rgba::rgb(average(0), average(1), average(2))
+            })
+            .collect()
+    }
+
+    /// Floyd-Steinberg dithering to the nearest `palette` colors, alpha is
+    /// ignored by the distance and the output pixels are the palette colors.
+    /// On the right and bottom borders, the error that would go out of the
+    /// image is dropped.
+    ///
+    /// # Panics
+    ///
+    /// When the palette is empty.
+    pub fn dither_to_palette(&self, palette: &[Rgba]) -> Image {
+        assert!(
+            !palette.is_empty(),
+            "Image::dither_to_palette() needs at least one color"
+        );
+
+        let width = self.width();
+        let mut values: Vec<[f32; 3]> = self
+            .iter()
+            .map(|pixel| {
+                let rgba = pixel.rgba();
+                [rgba.r as f32, rgba.g as f32, rgba.b as f32]
+            })
+            .collect();
+        let mut pixels = Vec::with_capacity(self.len());
+        for index in 0..values.len() {
+            let [r, g, b] = values[index].map(|value| (value.clamp(0., 255.) + 0.5) as u8);
+            let color = palette[nearest_color(palette, Rgba::rgb(r, g, b))];
+            pixels.push(Pixel::from(color));
+
+            let error = [
+                values[index][0] - color.r as f32,
+                values[index][1] - color.g as f32,
+                values[index][2] - color.b as f32,
+            ];
+            let x = index % width;
+            let has_right = x + 1 < width;
+            let has_below = index + width < values.len();
+            let neighbours = [
+                (has_right, index + 1, 7.),
+                (has_below && x > 0, (index + width).wrapping_sub(1), 3.),
+                (has_below, index + width, 5.),
+                (has_below && has_right, index + width + 1, 1.),
+            ];
+            for (exists, neighbour, weight) in neighbours {
+                if exists {
+                    for channel in 0..3 {
+                        values[neighbour][channel] += error[channel] * weight / 16.;
+                    }
+                }
+            }
+        }
+        Image::new(width, self.height(), pixels)
+    }
+}
+
+/// The channel with the largest range of values and that range
+fn longest_axis(colors: &[[u8; 3]]) -> (usize, u8) {
+    (0..3)
+        .map(|axis| {
+            let min = colors.iter().map(|color| color[axis]).min().unwrap_or(0);
+            let max = colors.iter().map(|color| color[axis]).max().unwrap_or(0);
+            (axis, max - min)
+        })
+        .max_by_key(|&(_, range)| range)
+        .unwrap_or((0, 0))
+}
+
+fn nearest_color(palette: &[Rgba], color: Rgba) -> usize {
+    let distance = |other: &Rgba| {
+        let delta = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        delta(color.r, other.r) + delta(color.g, other.g) + delta(color.b, other.b)
+    };
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, other)| distance(other))
+        .map_or(0, |(index, _)| index)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexedError {
+    DimensionOverflow,
+    LengthMismatch { expected: usize, actual: usize },
+    IndexOutOfPalette { index: u8, palette_len: usize },
+}
+
+impl Display for IndexedError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            IndexedError::DimensionOverflow => write!(f, "width * height overflows usize"),
+            IndexedError::LengthMismatch { expected, actual } => {
+                write!(f, "expected {expected} indices, found {actual}")
+            }
+            IndexedError::IndexOutOfPalette { index, palette_len } => {
+                write!(
+                    f,
+                    "index {index} is out of the {palette_len} colors palette"
+                )
+            }
+        }
+    }
+}
+
+impl Error for IndexedError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::String;
+
+    const PALETTE: [Rgba; 4] = [Rgba::BLACK, Rgba::RED, Rgba::GREEN, Rgba::BLUE];
+
+    #[test]
+    fn from_indexed() {
+        let image = Image::from_indexed(2, 2, &[3, 0, 1, 1], &PALETTE).unwrap();
+        let expected = [Rgba::BLUE, Rgba::BLACK, Rgba::RED, Rgba::RED].map(Pixel::from);
+        assert_eq!(image, (2, 2, &expected[..]));
+    }
+
+    #[test]
+    fn from_indexed_errors() {
+        assert_eq!(
+            Image::from_indexed(2, 2, &[3, 0, 4, 1], &PALETTE),
+            Err(IndexedError::IndexOutOfPalette {
+                index: 4,
+                palette_len: 4
+            })
+        );
+        assert_eq!(
+            Image::from_indexed(2, 2, &[3, 0, 1], &PALETTE),
+            Err(IndexedError::LengthMismatch {
+                expected: 4,
+                actual: 3
+            })
+        );
+        assert_eq!(
+            Image::from_indexed(usize::MAX, 2, &[], &PALETTE),
+            Err(IndexedError::DimensionOverflow)
+        );
+    }
+
+    #[test]
+    fn quantize_to_palette() {
+        let image = Image::new(
+            3,
+            2,
+            [
+                Rgba::RED,
+                Rgba::GREEN,
+                Rgba::BLUE,
+                Rgba::rgba(200, 30, 40, 0),
+                Rgba::rgb(10, 10, 250),
+                Rgba::rgb(20, 150, 20),
+            ]
+            .map(Pixel::from),
+        );
+        let palette = [Rgba::BLUE, Rgba::RED, Rgba::GREEN];
+        assert_eq!(image.quantize_to_palette(&palette), [1, 2, 0, 1, 0, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs between 1 and 256 colors, got 0")]
+    fn quantize_to_empty_palette() {
+        Image::new(1, 1, [Pixel::from(0)]).quantize_to_palette(&[]);
+    }
+
+    #[test]
+    fn generate_palette_of_two_colors() {
+        let red = Rgba::rgb(200, 10, 10);
+        let teal = Rgba::rgb(0, 128, 128);
+        let image = Image::new(3, 2, [red, red, red, teal, red, red].map(Pixel::from));
+
+        let mut palette = image.generate_palette(2);
+        palette.sort_by_key(|color| color.r);
+        assert_eq!(palette, [teal, red]);
+
+        assert_eq!(image.generate_palette(8).len(), 2);
+        assert_eq!(image.generate_palette(1), [Rgba::rgb(167, 30, 30)]);
+        assert!(image.generate_palette(0).is_empty());
+    }
+
+    #[test]
+    fn generate_palette_splits_longest_axis() {
+        let image = Image::new(
+            4,
+            1,
+            [
+                Rgba::rgb(0, 0, 0),
+                Rgba::rgb(10, 0, 100),
+                Rgba::rgb(0, 10, 200),
+                Rgba::rgb(10, 10, 255),
+            ]
+            .map(Pixel::from),
+        );
+        let mut palette = image.generate_palette(2);
+        palette.sort_by_key(|color| color.b);
+        assert_eq!(palette, [Rgba::rgb(5, 0, 50), Rgba::rgb(5, 10, 228)]);
+    }
+
+    fn dither_pattern(image: &Image) -> Vec<String> {
+        let res = image.dither_to_palette(&[Rgba::BLACK, Rgba::WHITE]);
+        res.rows()
+            .map(|row| {
+                row.iter()
+                    .map(|pixel| {
+                        if pixel.rgba() == Rgba::WHITE {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dither_gray_ramp() {
+        let ramp = [0, 85, 170, 255].map(|value| Pixel::from(Rgba::rgb(value, value, value)));
+        let image = Image::new(4, 4, [ramp; 4].concat());
+        assert_eq!(dither_pattern(&image), ["..##", "..##", "..##", ".#.#"]);
+
+        let gray = Image::new(4, 4, [Pixel::from(Rgba::rgb(128, 128, 128)); 16]);
+        assert_eq!(dither_pattern(&gray), ["#.#.", ".#.#", "#.#.", ".#.#"]);
+
+        let single_row = Image::new(4, 1, [Pixel::from(Rgba::rgb(128, 128, 128)); 4]);
+        assert_eq!(dither_pattern(&single_row), ["#.#."]);
+    }
+}
@@ -0,0 +1,212 @@
+use alloc::vec::Vec;
+
+use crate::Image;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const MAX_STORED_BLOCK_SIZE: usize = 0xffff;
+/// Decoders read the zlib stream chunk by chunk, keep each chunk small
+const MAX_IDAT_SIZE: usize = 1 << 20;
+/// PNG dimensions are stored in a u32 but can't be above 2^31 - 1
+const MAX_DIMENSION: u32 = i32::MAX as u32;
+
+impl Image {
+    /// Encodes the image as an 8 bit RGBA PNG, the pixel data is stored
+    /// without compression.
+    ///
+    /// # Panics
+    ///
+    /// When the width or height is 0 or above `i32::MAX`, which PNG doesn't
+    /// allow, the panic message starts with `Image::write_png()`.
+    pub fn write_png(&self) -> Vec<u8> {
+        let width = u32::try_from(self.width())
+            .ok()
+            .filter(|width| (1..=MAX_DIMENSION).contains(width))
+            .expect("Image::write_png() width must be in 1..=i32::MAX");
+        let height = u32::try_from(self.height())
+            .ok()
+            .filter(|height| (1..=MAX_DIMENSION).contains(height))
+            .expect("Image::write_png() height must be in 1..=i32::MAX");
+
+        let mut png = Vec::new();
+        png.extend_from_slice(&SIGNATURE);
+
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend_from_slice(&width.to_be_bytes());
+        ihdr.extend_from_slice(&height.to_be_bytes());
+        // Bit depth 8, color type 6 (RGBA), deflate, adaptive filtering, no interlace
+        ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+        write_chunk(&mut png, b"IHDR", &ihdr);
+
+        let mut scanlines = Vec::with_capacity(self.height() * (self.width() * 4 + 1));
+        for row in self.as_rgba_bytes().chunks_exact(self.width() * 4) {
+            // Filter type None
+            scanlines.push(0);
+            scanlines.extend_from_slice(row);
+        }
+        for idat in zlib_stored(&scanlines).chunks(MAX_IDAT_SIZE) {
+            write_chunk(&mut png, b"IDAT", idat);
+        }
+
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+fn write_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    let len = u32::try_from(data.len()).expect("PNG chunk is larger than u32::MAX");
+    png.extend_from_slice(&len.to_be_bytes());
+    let crc_start = png.len();
+    png.extend_from_slice(chunk_type);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[crc_start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let block_count = data.len().div_ceil(MAX_STORED_BLOCK_SIZE).max(1);
+    let mut zlib = Vec::with_capacity(2 + data.len() + block_count * 5 + 4);
+    // Deflate with a 32K window, no preset dictionary, fastest compression
+    zlib.extend_from_slice(&[0x78, 0x01]);
+
+    let mut blocks = data.chunks(MAX_STORED_BLOCK_SIZE).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let len = block.len() as u16;
+        // BFINAL bit then BTYPE 00 (stored)
+        zlib.push(is_final as u8);
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+
+    zlib.extend_from_slice(&adler32(data).to_be_bytes());
+    zlib
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut n = 0;
+        while n < 256 {
+            let mut c = n as u32;
+            let mut k = 0;
+            while k < 8 {
+                c = if c & 1 != 0 {
+                    0xedb88320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+                k += 1;
+            }
+            table[n] = c;
+            n += 1;
+        }
+        table
+    };
+
+    !data.iter().fold(!0u32, |crc, byte| {
+        TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+    // Largest number of bytes that can be summed before b can overflow a u32
+    const NMAX: usize = 5552;
+
+    let mut a = 1u32;
+    let mut b = 0u32;
+    for chunk in data.chunks(NMAX) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::image::{Pixel, Rgba};
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(crc32(b"IEND"), 0xae426082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn decodes_with_png_crate() {
+        let data: Vec<Pixel> = (0..7u32 * 5)
+            .map(|i| {
+                Pixel::from(Rgba {
+                    r: i as u8,
+                    g: (i * 7) as u8,
+                    b: 255 - i as u8,
+                    a: (i * 13) as u8,
+                })
+            })
+            .collect();
+        let image = Image::new(7, 5, data);
+        let png = image.write_png();
+
+        let decoder = png::Decoder::new(png.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).unwrap();
+        assert_eq!(info.width, 7);
+        assert_eq!(info.height, 5);
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+        assert_eq!(info.bit_depth, png::BitDepth::Eight);
+        assert_eq!(&buffer[..info.buffer_size()], image.as_rgba_bytes());
+    }
+
+    #[test]
+    fn multiple_stored_blocks() {
+        let image = Image::filled(200, 100, Pixel::from(0x12345678)).unwrap();
+        let png = image.write_png();
+
+        let decoder = png::Decoder::new(png.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).unwrap();
+        assert_eq!(&buffer[..info.buffer_size()], image.as_rgba_bytes());
+    }
+
+    #[test]
+    fn multiple_idat_chunks() {
+        let image = Image::filled(600, 500, Pixel::from(0x12345678)).unwrap();
+        let png = image.write_png();
+
+        let mut idat_sizes = Vec::new();
+        let mut chunks = &png[SIGNATURE.len()..];
+        while !chunks.is_empty() {
+            let len = u32::from_be_bytes(chunks[..4].try_into().unwrap()) as usize;
+            if &chunks[4..8] == b"IDAT" {
+                idat_sizes.push(len);
+            }
+            chunks = &chunks[12 + len..];
+        }
+        assert_eq!(idat_sizes.len(), 2);
+        assert_eq!(idat_sizes[0], MAX_IDAT_SIZE);
+
+        let decoder = png::Decoder::new(png.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).unwrap();
+        assert_eq!(&buffer[..info.buffer_size()], image.as_rgba_bytes());
+    }
+
+    #[test]
+    #[should_panic(expected = "Image::write_png() width must be in 1..=i32::MAX")]
+    fn width_above_i32_max() {
+        Image::new(MAX_DIMENSION as usize + 1, 0, []).write_png();
+    }
+}
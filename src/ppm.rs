@@ -1,22 +1,45 @@
-use core::str;
+use alloc::{
+    collections::TryReserveError,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    error::Error,
+    fmt::{Display, Write},
+    num::ParseIntError,
+    ops::ControlFlow,
+    str,
+    str::Utf8Error,
+};
+#[cfg(feature = "std")]
 use std::{
-    collections::TryReserveError, error::Error, fmt::Display, fs::File, io::Read,
-    num::ParseIntError, str::Utf8Error,
+    fs::{File, OpenOptions},
+    io::{BufRead, Read, Write as _},
 };
 
 use crate::{
     image::{Pixel, Rgba, DEFAULT_ALPHA_VALUE},
-    Image,
+    Channels, GrayImage, Image, Image16, Rgb16,
 };
 
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod simd;
+
+/// The digits of u64::MAX, no valid width, height or maxval is longer
+const MAX_NUMERIC_FIELD_LEN: usize = 20;
+
+#[cfg(feature = "std")]
 pub struct PpmFilePath<'a>(pub &'a str);
 
 #[derive(Debug)]
 pub enum ParsingError {
+    #[cfg(feature = "std")]
     FailedToOpenFile(std::io::Error),
+    #[cfg(feature = "std")]
     FailedToReadFile(std::io::Error),
 
     FormatNotFound,
+    OnlyCommentsNoImage,
     NoWhitespaceAfterFormat,
     FormatNotSupported,
 
@@ -34,61 +57,431 @@ pub enum ParsingError {
     SizeMulColorByteCountOverflows,
 
     MaxvalNotFound,
+    /// Never returned, the end of the input ends the maxval field like any
+    /// other whitespace. Kept so that existing matches still compile.
+    #[deprecated(note = "the end of the input now ends the maxval field")]
     NoWhitespaceAfterMaxval,
     MaxvalIsNotAUtf8String(Utf8Error),
     MaxvalIsNotAU16(ParseIntError),
     MaxvalCantBe0,
 
+    NumericFieldTooLong,
+
+    ImageTooLargeForPlatform,
     FailedToAllocateImageData(TryReserveError),
     LessThanSizePixelsFoundInFile,
+    SampleExceedsMaxval,
+    SampleIsNotAU16(ParseIntError),
+
+    TooManyImages,
+    DimensionsExceedLimit,
+    PixelsExceedLimit,
+    TotalPixelsExceedLimit {
+        image_index: usize,
+    },
+
+    TrailingGarbage {
+        offset: usize,
+    },
 }
 
 impl Display for ParsingError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            ParsingError::FailedToOpenFile(err) => write!(f, "failed to open file: {err}"),
+            #[cfg(feature = "std")]
+            ParsingError::FailedToReadFile(err) => write!(f, "failed to read file: {err}"),
+
+            ParsingError::FormatNotFound => write!(f, "PPM format field not found"),
+            ParsingError::OnlyCommentsNoImage => {
+                write!(f, "PPM file only holds comments, no image")
+            }
+            ParsingError::NoWhitespaceAfterFormat => {
+                write!(f, "PPM format field is not followed by whitespace")
+            }
+            ParsingError::FormatNotSupported => write!(f, "PPM format is not supported"),
+
+            ParsingError::WidthNotFound => write!(f, "PPM width field not found"),
+            ParsingError::NoWhitespaceAfterWidth => {
+                write!(f, "PPM width field is not followed by whitespace")
+            }
+            ParsingError::WidthIsNotAUtf8String(err) => {
+                write!(f, "PPM width field is not valid UTF-8: {err}")
+            }
+            ParsingError::WidthIsNotAUsize(err) => {
+                write!(f, "PPM width field is not a valid unsigned integer: {err}")
+            }
+
+            ParsingError::HeightNotFound => write!(f, "PPM height field not found"),
+            ParsingError::NoWhitespaceAfterHeight => {
+                write!(f, "PPM height field is not followed by whitespace")
+            }
+            ParsingError::HeightIsNotAUtf8String(err) => {
+                write!(f, "PPM height field is not valid UTF-8: {err}")
+            }
+            ParsingError::HeightIsNotAUsize(err) => {
+                write!(f, "PPM height field is not a valid unsigned integer: {err}")
+            }
+
+            ParsingError::WidthMulHeightOverflowsUsize => {
+                write!(f, "PPM width * height overflows usize")
+            }
+            ParsingError::SizeMulColorByteCountOverflows => {
+                write!(f, "PPM raster byte count overflows usize")
+            }
+
+            ParsingError::MaxvalNotFound => write!(f, "PPM maxval field not found"),
+            #[allow(deprecated)]
+            ParsingError::NoWhitespaceAfterMaxval => {
+                write!(f, "PPM maxval field is not followed by whitespace")
+            }
+            ParsingError::MaxvalIsNotAUtf8String(err) => {
+                write!(f, "PPM maxval field is not valid UTF-8: {err}")
+            }
+            ParsingError::MaxvalIsNotAU16(err) => {
+                write!(
+                    f,
+                    "PPM maxval field is not a valid 16 bit unsigned integer: {err}"
+                )
+            }
+            ParsingError::MaxvalCantBe0 => write!(f, "PPM maxval can't be 0"),
+
+            ParsingError::NumericFieldTooLong => write!(
+                f,
+                "PPM numeric field is longer than {MAX_NUMERIC_FIELD_LEN} bytes"
+            ),
+
+            ParsingError::ImageTooLargeForPlatform => write!(
+                f,
+                "PPM image pixel data would be larger than isize::MAX bytes"
+            ),
+            ParsingError::FailedToAllocateImageData(err) => {
+                write!(f, "failed to allocate image data: {err}")
+            }
+            ParsingError::LessThanSizePixelsFoundInFile => {
+                write!(f, "PPM raster contains less than width * height pixels")
+            }
+            ParsingError::SampleExceedsMaxval => {
+                write!(f, "PPM raster contains a sample larger than the maxval")
+            }
+            ParsingError::SampleIsNotAU16(err) => {
+                write!(
+                    f,
+                    "PPM raster sample is not a valid 16 bit unsigned integer: {err}"
+                )
+            }
+
+            ParsingError::TooManyImages => write!(f, "PPM file contains too many images"),
+            ParsingError::DimensionsExceedLimit => {
+                write!(f, "PPM image width or height is larger than allowed")
+            }
+            ParsingError::PixelsExceedLimit => {
+                write!(f, "PPM image contains more pixels than allowed")
+            }
+            ParsingError::TotalPixelsExceedLimit { image_index } => write!(
+                f,
+                "PPM image {image_index} brings the file over the total pixel limit"
+            ),
+
+            ParsingError::TrailingGarbage { offset } => {
+                write!(
+                    f,
+                    "PPM file has trailing data that isn't an image at byte {offset}"
+                )
+            }
+        }
+    }
+}
+
+impl Error for ParsingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            ParsingError::FailedToOpenFile(err) | ParsingError::FailedToReadFile(err) => Some(err),
+            ParsingError::WidthIsNotAUtf8String(err)
+            | ParsingError::HeightIsNotAUtf8String(err)
+            | ParsingError::MaxvalIsNotAUtf8String(err) => Some(err),
+            ParsingError::WidthIsNotAUsize(err)
+            | ParsingError::HeightIsNotAUsize(err)
+            | ParsingError::MaxvalIsNotAU16(err)
+            | ParsingError::SampleIsNotAU16(err) => Some(err),
+            ParsingError::FailedToAllocateImageData(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MaskError {
+    Parsing(ParsingError),
+    DimensionMismatch {
+        image: (usize, usize),
+        mask: (usize, usize),
+    },
+}
+
+impl Display for MaskError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MaskError::Parsing(err) => write!(f, "failed to parse the PGM mask: {err}"),
+            MaskError::DimensionMismatch { image, mask } => write!(
+                f,
+                "the {}x{} mask doesn't match the {}x{} image",
+                mask.0, mask.1, image.0, image.1
+            ),
+        }
+    }
+}
+
+impl Error for MaskError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MaskError::Parsing(err) => Some(err),
+            MaskError::DimensionMismatch { .. } => None,
+        }
+    }
+}
+
+/// Limits checked against each header before any pixel data is allocated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub max_images: usize,
+    pub max_width: usize,
+    pub max_height: usize,
+    pub max_pixels: usize,
+    pub max_total_pixels: usize,
+    /// Report data after the first image that isn't a valid header as
+    /// `TrailingGarbage` instead of the error the header parsing ran into
+    pub strict: bool,
+    /// Alpha of the pixels of the formats without an alpha channel. Only
+    /// parse_ppm_file_with_options() reads it, every other entry point uses
+    /// [`DEFAULT_ALPHA_VALUE`], which both unlimited() and default() also use.
+    pub default_alpha: u8,
+    /// Report samples larger than the maxval as `SampleExceedsMaxval` instead
+    /// of clamping them to the maxval
+    pub reject_samples_above_maxval: bool,
+}
+
+impl ParseOptions {
+    /// No limits, parse_ppm_file() uses these options
+    pub const fn unlimited() -> Self {
+        Self {
+            max_images: usize::MAX,
+            max_width: usize::MAX,
+            max_height: usize::MAX,
+            max_pixels: usize::MAX,
+            max_total_pixels: usize::MAX,
+            strict: false,
+            default_alpha: DEFAULT_ALPHA_VALUE,
+            reject_samples_above_maxval: false,
+        }
+    }
+}
+
+/// Generous limits that still keep a single image under 1 GiB of pixel data
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_width: 1 << 17,
+            max_height: 1 << 17,
+            max_pixels: 1 << 28,
+            ..Self::unlimited()
+        }
+    }
+}
+
+/// The Netpbm subformats, named after their magic number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PpmFormat {
+    /// Plain (ASCII) bitmap, PBM
+    P1,
+    /// Plain (ASCII) graymap, PGM
+    P2,
+    /// Plain (ASCII) pixmap, PPM
+    P3,
+    /// Binary bitmap, PBM
+    P4,
+    /// Binary graymap, PGM
+    P5,
+    /// Binary pixmap, PPM
+    P6,
+    /// Arbitrary map, PAM
+    P7,
+}
+
+impl PpmFormat {
+    /// The magic has to be exactly the two bytes of the magic number
+    pub fn from_magic(magic: &[u8]) -> Option<PpmFormat> {
+        match magic {
+            b"P1" => Some(PpmFormat::P1),
+            b"P2" => Some(PpmFormat::P2),
+            b"P3" => Some(PpmFormat::P3),
+            b"P4" => Some(PpmFormat::P4),
+            b"P5" => Some(PpmFormat::P5),
+            b"P6" => Some(PpmFormat::P6),
+            b"P7" => Some(PpmFormat::P7),
+            _ => None,
+        }
+    }
+
+    /// The channels stored per pixel, None for P7 since its header declares
+    /// them with its DEPTH and TUPLTYPE fields
+    pub fn channels(&self) -> Option<Channels> {
+        match self {
+            PpmFormat::P1 | PpmFormat::P2 | PpmFormat::P4 | PpmFormat::P5 => Some(Channels::Gray),
+            PpmFormat::P3 | PpmFormat::P6 => Some(Channels::Rgb),
+            PpmFormat::P7 => None,
+        }
+    }
+
+    /// Whether the raster is stored as bytes instead of ASCII decimal numbers
+    pub fn is_binary(&self) -> bool {
+        !matches!(self, PpmFormat::P1 | PpmFormat::P2 | PpmFormat::P3)
+    }
+
+    /// Bitmaps have no maxval field, their samples are a single bit
+    pub fn has_maxval(&self) -> bool {
+        !matches!(self, PpmFormat::P1 | PpmFormat::P4)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PpmMeta {
+    pub magic: [u8; 2],
+    pub maxval: u16,
+}
+
+impl PpmMeta {
+    pub fn format(&self) -> Option<PpmFormat> {
+        PpmFormat::from_magic(&self.magic)
+    }
+}
+
+/// The bytes around the fields of a header, comments included: before the
+/// magic number, then after the magic number, the width, the height and the
+/// maxval. The default is the canonical `P6\n<width> <height>\n255\n` layout.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HeaderLayout {
+    pub separators: [Vec<u8>; 5],
+}
+
+impl Default for HeaderLayout {
+    fn default() -> Self {
+        Self {
+            separators: [
+                Vec::new(),
+                b"\n".to_vec(),
+                b" ".to_vec(),
+                b"\n".to_vec(),
+                b"\n".to_vec(),
+            ],
+        }
     }
 }
 
-impl Error for ParsingError {}
+#[derive(Debug, Default)]
+pub struct PartialHeader<'a> {
+    pub magic: Option<&'a [u8]>,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub maxval: Option<u16>,
+    pub error: Option<ParsingError>,
+}
+
+struct Header<'a> {
+    magic: &'a [u8],
+    format: Option<PpmFormat>,
+    width: usize,
+    height: usize,
+    size: usize,
+    maxval: u16,
+    raster_offset: usize,
+}
 
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub struct ImagesFromPpmFileError {
-    #[allow(dead_code)]
     parsing_error: ParsingError,
-    #[allow(dead_code)]
     file_name: String,
 }
 
+#[cfg(feature = "std")]
 impl Display for ImagesFromPpmFileError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}: {}", self.file_name, self.parsing_error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ImagesFromPpmFileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.parsing_error)
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum SaveError {
+    MaxvalCantBe0,
+    FailedToOpenFile(std::io::Error),
+    FailedToWriteFile(std::io::Error),
+}
+
+#[cfg(feature = "std")]
+impl Display for SaveError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SaveError::MaxvalCantBe0 => write!(f, "PPM maxval can't be 0"),
+            SaveError::FailedToOpenFile(err) => write!(f, "failed to open file: {err}"),
+            SaveError::FailedToWriteFile(err) => write!(f, "failed to write file: {err}"),
+        }
     }
 }
 
-impl Error for ImagesFromPpmFileError {}
+#[cfg(feature = "std")]
+impl Error for SaveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SaveError::MaxvalCantBe0 => None,
+            SaveError::FailedToOpenFile(err) | SaveError::FailedToWriteFile(err) => Some(err),
+        }
+    }
+}
 
+#[cfg(feature = "std")]
 impl TryFrom<PpmFilePath<'_>> for Vec<Image> {
     type Error = ImagesFromPpmFileError;
 
     fn try_from(file_path: PpmFilePath) -> Result<Self, Self::Error> {
-        let mut file = File::open(file_path.0).map_err(|err| ImagesFromPpmFileError {
+        let file = File::open(file_path.0).map_err(|err| ImagesFromPpmFileError {
             parsing_error: ParsingError::FailedToOpenFile(err),
             file_name: file_path.0.to_string(),
         })?;
-        let mut file_content = Vec::new();
-        file.read_to_end(&mut file_content)
-            .map_err(|err| ImagesFromPpmFileError {
-                parsing_error: ParsingError::FailedToReadFile(err),
-                file_name: file_path.0.to_string(),
-            })?;
-
-        parse_ppm_file(&file_content).map_err(|parsing_error| ImagesFromPpmFileError {
-            parsing_error,
-            file_name: file_path.0.to_string(),
-        })
+        read_ppm_images(file, file_path.0)
     }
 }
 
+#[cfg(feature = "std")]
+fn read_ppm_images(
+    mut reader: impl Read,
+    file_name: &str,
+) -> Result<Vec<Image>, ImagesFromPpmFileError> {
+    let mut file_content = Vec::new();
+    reader
+        .read_to_end(&mut file_content)
+        .map_err(|err| ImagesFromPpmFileError {
+            parsing_error: ParsingError::FailedToReadFile(err),
+            file_name: file_name.to_string(),
+        })?;
+
+    parse_ppm_file(&file_content).map_err(|parsing_error| ImagesFromPpmFileError {
+        parsing_error,
+        file_name: file_name.to_string(),
+    })
+}
+
+#[cfg(feature = "std")]
 impl TryFrom<PpmFilePath<'_>> for Image {
     type Error = ImagesFromPpmFileError;
 
@@ -100,193 +493,1089 @@ impl TryFrom<PpmFilePath<'_>> for Image {
     }
 }
 
-fn parse_ppm_file(file_content: &[u8]) -> Result<Vec<Image>, ParsingError> {
-    let mut images = Vec::new();
+impl Image {
+    /// Plain (ASCII) P3 encoding with one pixel per line, alpha is dropped
+    ///
+    /// ```
+    /// use image_parser::{Image, Rgba};
+    ///
+    /// let image = Image::new(1, 1, [Rgba::RED.into()]);
+    /// assert_eq!(image.to_p3_string(), "P3\n1 1\n255\n255 0 0\n");
+    /// ```
+    pub fn to_p3_string(&self) -> String {
+        let mut output = String::new();
+        // Writing into a String can't fail
+        let _ = writeln!(output, "P3\n{} {}\n255", self.width(), self.height());
+        for pixel in self.iter() {
+            let rgba = pixel.rgba();
+            let _ = writeln!(output, "{} {} {}", rgba.r, rgba.g, rgba.b);
+        }
+        output
+    }
 
-    if file_content.is_empty() {
-        return Err(ParsingError::FormatNotFound);
+    /// Replaces the alpha of each pixel by the gray value of the same pixel of
+    /// the first image of a PGM (P5 or P2) file, scaled to a maxval of 255
+    pub fn with_alpha_from_pgm(&mut self, pgm_bytes: &[u8]) -> Result<(), MaskError> {
+        let header =
+            parse_header(pgm_bytes, &mut PartialHeader::default()).map_err(MaskError::Parsing)?;
+        if !matches!(header.format, Some(PpmFormat::P5 | PpmFormat::P2)) {
+            return Err(MaskError::Parsing(ParsingError::FormatNotSupported));
+        }
+        if (header.width, header.height) != (self.width(), self.height()) {
+            return Err(MaskError::DimensionMismatch {
+                image: (self.width(), self.height()),
+                mask: (header.width, header.height),
+            });
+        }
+
+        let (_, mask) = read_gray_image(pgm_bytes, &header).map_err(MaskError::Parsing)?;
+        for (pixel, &alpha) in self.iter_mut().zip(mask.pixels()) {
+            pixel.set_a(alpha);
+        }
+        Ok(())
     }
 
-    let mut cursor = 0;
-    while cursor < file_content.len() {
-        let (bytes_read, image) = parse_image(&file_content[cursor..])?;
+    /// Binary P6 encoding with a maxval of 255, alpha is dropped
+    ///
+    /// ```
+    /// use image_parser::{Image, Rgba};
+    ///
+    /// let image = Image::new(1, 1, [Rgba::RED.into()]);
+    /// assert_eq!(image.to_p6(), b"P6\n1 1\n255\n\xff\x00\x00");
+    /// ```
+    pub fn to_p6(&self) -> Vec<u8> {
+        self.to_p6_with_layout(&HeaderLayout::default())
+    }
 
-        images.push(image);
+    /// Same as to_p6() with the header laid out as `layout`. A file with a
+    /// maxval of 255 and no leading zeros is written back byte for byte with
+    /// the layout returned by parse_ppm_file_with_layout().
+    pub fn to_p6_with_layout(&self, layout: &HeaderLayout) -> Vec<u8> {
+        self.write_p6(layout, 255)
+    }
 
-        match get_content_start_index(file_content, cursor + bytes_read) {
-            Some(index) => cursor = index,
-            None => break,
+    /// The samples are scaled from 255 to `maxval`, which must not be 0
+    fn write_p6(&self, layout: &HeaderLayout, maxval: u16) -> Vec<u8> {
+        let [leading, after_magic, after_width, after_height, after_maxval] = &layout.separators;
+        let mut output = Vec::new();
+        output.extend_from_slice(leading);
+        output.extend_from_slice(b"P6");
+        output.extend_from_slice(after_magic);
+        output.extend_from_slice(self.width().to_string().as_bytes());
+        output.extend_from_slice(after_width);
+        output.extend_from_slice(self.height().to_string().as_bytes());
+        output.extend_from_slice(after_height);
+        output.extend_from_slice(maxval.to_string().as_bytes());
+        output.extend_from_slice(after_maxval);
+        let scale = |sample: u8| ((sample as u32 * maxval as u32 + 127) / 255) as u16;
+        for pixel in self.iter() {
+            let rgba = pixel.rgba();
+            for sample in [rgba.r, rgba.g, rgba.b].map(scale) {
+                if maxval < 256 {
+                    output.push(sample as u8);
+                } else {
+                    output.extend_from_slice(&sample.to_be_bytes());
+                }
+            }
         }
+        output
     }
-
-    Ok(images)
 }
 
-fn parse_image(file_content: &[u8]) -> Result<(usize, Image), ParsingError> {
-    let mut start = get_content_start_index(file_content, 0).ok_or(ParsingError::FormatNotFound)?;
-    let mut end =
-        get_content_end_index(file_content, start).ok_or(ParsingError::NoWhitespaceAfterFormat)?;
-    let format = &file_content[start..end];
+#[cfg(feature = "std")]
+impl Image {
+    /// Writes the image as a P6 frame with its samples scaled to `maxval` at
+    /// the end of the file, which is created if it doesn't exist. Alpha is
+    /// dropped.
+    pub fn append_ppm(&self, path: &str, maxval: u16) -> Result<(), SaveError> {
+        if maxval == 0 {
+            return Err(SaveError::MaxvalCantBe0);
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(SaveError::FailedToOpenFile)?;
+        file.write_all(&self.write_p6(&HeaderLayout::default(), maxval))
+            .map_err(SaveError::FailedToWriteFile)
+    }
 
-    start = get_content_start_index(file_content, end).ok_or(ParsingError::WidthNotFound)?;
-    end = get_content_end_index(file_content, start).ok_or(ParsingError::NoWhitespaceAfterWidth)?;
-    let width = str::from_utf8(&file_content[start..end])
-        .map_err(ParsingError::WidthIsNotAUtf8String)?
-        .parse::<usize>()
-        .map_err(ParsingError::WidthIsNotAUsize)?;
+    /// Reads stdin to its end and parses it like a file, the errors are
+    /// reported with `<stdin>` as their file name
+    pub fn from_stdin() -> Result<Vec<Image>, ImagesFromPpmFileError> {
+        read_ppm_images(std::io::stdin().lock(), "<stdin>")
+    }
 
-    start = get_content_start_index(file_content, end).ok_or(ParsingError::HeightNotFound)?;
-    end =
-        get_content_end_index(file_content, start).ok_or(ParsingError::NoWhitespaceAfterHeight)?;
-    let height = str::from_utf8(&file_content[start..end])
-        .map_err(ParsingError::HeightIsNotAUtf8String)?
-        .parse::<usize>()
-        .map_err(ParsingError::HeightIsNotAUsize)?;
+    /// Decodes the first image of a PPM stream without reading the whole file
+    /// in memory: the raster is converted as it comes out of the reader's
+    /// buffer, so the peak memory is the pixel buffer plus the reader's capacity
+    pub fn from_ppm_reader_streaming<R: BufRead>(mut reader: R) -> Result<Image, ParsingError> {
+        let format = read_header_field(
+            &mut reader,
+            is_field_end,
+            ParsingError::FormatNotFound,
+            Some(ParsingError::NoWhitespaceAfterFormat),
+        )?;
+        let width: usize = parse_field(
+            &read_header_field(
+                &mut reader,
+                is_field_end,
+                ParsingError::WidthNotFound,
+                Some(ParsingError::NoWhitespaceAfterWidth),
+            )?,
+            ParsingError::WidthIsNotAUtf8String,
+            ParsingError::WidthIsNotAUsize,
+        )?;
+        let height: usize = parse_field(
+            &read_header_field(
+                &mut reader,
+                is_field_end,
+                ParsingError::HeightNotFound,
+                Some(ParsingError::NoWhitespaceAfterHeight),
+            )?,
+            ParsingError::HeightIsNotAUtf8String,
+            ParsingError::HeightIsNotAUsize,
+        )?;
+        let size = width
+            .checked_mul(height)
+            .ok_or(ParsingError::WidthMulHeightOverflowsUsize)?;
+        let maxval: u16 = parse_field(
+            &read_header_field(
+                &mut reader,
+                |byte| (byte as char).is_whitespace(),
+                ParsingError::MaxvalNotFound,
+                None,
+            )?,
+            ParsingError::MaxvalIsNotAUtf8String,
+            ParsingError::MaxvalIsNotAU16,
+        )?;
+        if maxval == 0 {
+            return Err(ParsingError::MaxvalCantBe0);
+        }
+        // The single whitespace that separates the header from the raster
+        if peek_byte(&mut reader)?.is_some() {
+            reader.consume(1);
+        }
 
-    let size = width
-        .checked_mul(height)
-        .ok_or(ParsingError::WidthMulHeightOverflowsUsize)?;
+        let header = Header {
+            magic: &format,
+            format: PpmFormat::from_magic(&format),
+            width,
+            height,
+            size,
+            maxval,
+            raster_offset: 0,
+        };
+        let raster_len = raster_len(&header)?;
+        if size == 0 {
+            return Ok(Image::new(width, height, []));
+        }
+        let color_len = raster_len / size;
+        check_fits_platform(size)?;
+
+        let mut image_data = Vec::<Pixel>::new();
+        image_data
+            .try_reserve_exact(size)
+            .map_err(ParsingError::FailedToAllocateImageData)?;
+
+        // Holds a pixel split between two fills of the reader's buffer
+        let mut pending = [0; 6];
+        let mut pending_len = 0;
+        let mut remaining = raster_len;
+        while remaining != 0 {
+            let buffer = reader.fill_buf().map_err(ParsingError::FailedToReadFile)?;
+            if buffer.is_empty() {
+                return Err(ParsingError::LessThanSizePixelsFoundInFile);
+            }
+            let buffer = &buffer[..buffer.len().min(remaining)];
+
+            let mut start = 0;
+            if pending_len != 0 {
+                start = (color_len - pending_len).min(buffer.len());
+                pending[pending_len..pending_len + start].copy_from_slice(&buffer[..start]);
+                pending_len += start;
+                if pending_len == color_len {
+                    image_data.push(decode_color(&pending[..color_len], maxval));
+                    pending_len = 0;
+                }
+            }
+            let colors = buffer[start..].chunks_exact(color_len);
+            let rest = colors.remainder();
+            pending[pending_len..pending_len + rest.len()].copy_from_slice(rest);
+            pending_len += rest.len();
+            image_data.extend(colors.map(|color| decode_color(color, maxval)));
+
+            let consumed = buffer.len();
+            reader.consume(consumed);
+            remaining -= consumed;
+        }
 
-    start = get_content_start_index(file_content, end).ok_or(ParsingError::MaxvalNotFound)?;
-    end = find_index(file_content, start, |elem| (elem as char).is_whitespace())
-        .ok_or(ParsingError::NoWhitespaceAfterMaxval)?;
-    let maxval = str::from_utf8(&file_content[start..end])
-        .map_err(ParsingError::MaxvalIsNotAUtf8String)?
-        .parse::<u16>()
-        .map_err(ParsingError::MaxvalIsNotAU16)?;
-    if maxval == 0 {
-        return Err(ParsingError::MaxvalCantBe0);
+        Ok(Image::new(width, height, image_data))
     }
-
-    start = end + 1;
-    let (bytes_read, image) = match format {
-        b"P6" => read_image(&file_content[start..], width, height, size, maxval)?,
-        _ => return Err(ParsingError::FormatNotSupported),
-    };
-    Ok((start + bytes_read, image))
 }
 
-fn read_image(
-    raw_image_data: &[u8],
-    width: usize,
-    height: usize,
-    size: usize,
-    maxval: u16,
-) -> Result<(usize, Image), ParsingError> {
-    let mut image_data = Vec::<Pixel>::new();
-    image_data
-        .try_reserve_exact(size)
-        .map_err(ParsingError::FailedToAllocateImageData)?;
+#[cfg(feature = "std")]
+fn read_header_field<R: BufRead>(
+    reader: &mut R,
+    is_end: impl Fn(u8) -> bool,
+    not_found: ParsingError,
+    no_whitespace_after: Option<ParsingError>,
+) -> Result<Vec<u8>, ParsingError> {
+    let mut in_comment = false;
+    loop {
+        match peek_byte(reader)? {
+            None => return Err(not_found),
+            Some(b'\n') if in_comment => in_comment = false,
+            Some(_) if in_comment => {}
+            Some(b'#') => in_comment = true,
+            Some(byte) if (byte as char).is_whitespace() => {}
+            Some(_) => break,
+        }
+        reader.consume(1);
+    }
 
-    // TODO consider handling the case of maxval 255
-    let bytes_read = if maxval < 256 {
-        read_image_from_u8_maxval(raw_image_data, size, maxval as u8, &mut image_data)?
-    } else {
-        read_image_from_u16_maxval(raw_image_data, size, maxval, &mut image_data)?
-    };
+    let mut field = Vec::new();
+    loop {
+        match peek_byte(reader)? {
+            None => return no_whitespace_after.map_or(Ok(field), Err),
+            Some(byte) if is_end(byte) => return Ok(field),
+            // No valid field is this long, the magic included
+            Some(_) if field.len() == MAX_NUMERIC_FIELD_LEN => {
+                return Err(ParsingError::NumericFieldTooLong)
+            }
+            Some(byte) => {
+                field.push(byte);
+                reader.consume(1);
+            }
+        }
+    }
+}
 
-    Ok((bytes_read, Image::new(width, height, image_data)))
+#[cfg(feature = "std")]
+fn peek_byte<R: BufRead>(reader: &mut R) -> Result<Option<u8>, ParsingError> {
+    Ok(reader
+        .fill_buf()
+        .map_err(ParsingError::FailedToReadFile)?
+        .first()
+        .copied())
 }
 
-fn read_image_from_u8_maxval(
-    raw_image_data: &[u8],
-    size: usize,
-    maxval: u8,
-    image_data: &mut Vec<Pixel>,
-) -> Result<usize, ParsingError> {
-    const SIZE_OF_U8_COLOR: usize = 3;
-    let limit = size
-        .checked_mul(SIZE_OF_U8_COLOR)
-        .ok_or(ParsingError::SizeMulColorByteCountOverflows)?;
+#[cfg(feature = "std")]
+fn is_field_end(byte: u8) -> bool {
+    (byte as char).is_whitespace() || byte == b'#'
+}
 
-    if raw_image_data.len() < limit {
-        return Err(ParsingError::LessThanSizePixelsFoundInFile);
+#[cfg(feature = "std")]
+fn decode_color(color: &[u8], maxval: u16) -> Pixel {
+    if maxval < 256 {
+        u8_color_to_pixel(color, maxval as u8, DEFAULT_ALPHA_VALUE)
+    } else {
+        u16_color_to_pixel(color, maxval, DEFAULT_ALPHA_VALUE)
     }
+}
 
-    for i in (2..limit).step_by(SIZE_OF_U8_COLOR) {
-        image_data.push(Pixel {
-            rgba: Rgba {
-                r: convert_u8_maxval_color(raw_image_data[i - 2], maxval),
-                g: convert_u8_maxval_color(raw_image_data[i - 1], maxval),
-                b: convert_u8_maxval_color(raw_image_data[i], maxval),
-                a: DEFAULT_ALPHA_VALUE,
-            },
-        });
-    }
+/// Decodes the first image of the file into `out`, replacing its content, and
+/// returns its `(width, height)`. Reusing the same Vec for many frames avoids
+/// allocating a new pixel buffer for each of them.
+pub fn parse_ppm_into(
+    file_content: &[u8],
+    out: &mut Vec<Pixel>,
+) -> Result<(usize, usize), ParsingError> {
+    let header = parse_header(file_content, &mut PartialHeader::default())?;
+    raster_len(&header)?;
+
+    out.clear();
+    read_pixels(
+        &file_content[header.raster_offset..],
+        header.size,
+        header.maxval,
+        DEFAULT_ALPHA_VALUE,
+        out,
+    )?;
+    Ok((header.width, header.height))
+}
 
-    Ok(limit)
+/// Decodes the first image of the file into tightly packed RGB bytes, three
+/// per pixel, and returns its `(width, height, rgb)`. The colors are scaled to
+/// a maxval of 255 as with parse_ppm_file(), and no alpha is stored.
+pub fn decode_ppm_to_rgb(file_content: &[u8]) -> Result<(usize, usize, Vec<u8>), ParsingError> {
+    let header = parse_header(file_content, &mut PartialHeader::default())?;
+    let raster_len = raster_len(&header)?;
+    let raster = file_content[header.raster_offset..]
+        .get(..raster_len)
+        .ok_or(ParsingError::LessThanSizePixelsFoundInFile)?;
+
+    let mut rgb = Vec::new();
+    rgb.try_reserve_exact(header.size * 3)
+        .map_err(ParsingError::FailedToAllocateImageData)?;
+    if header.maxval < 256 {
+        let maxval = header.maxval as u8;
+        rgb.extend(
+            raster
+                .iter()
+                .map(|&sample| convert_u8_maxval_color(sample, maxval)),
+        );
+    } else {
+        rgb.extend(raster.chunks_exact(2).map(|sample| {
+            convert_u16_maxval_color(u16::from_be_bytes([sample[0], sample[1]]), header.maxval)
+        }));
+    }
+    Ok((header.width, header.height, rgb))
 }
 
-fn read_image_from_u16_maxval(
-    raw_image_data: &[u8],
-    size: usize,
-    maxval: u16,
-    image_data: &mut Vec<Pixel>,
-) -> Result<usize, ParsingError> {
-    const SIZE_OF_U16_COLOR: usize = 6;
-    let limit = size
-        .checked_mul(SIZE_OF_U16_COLOR)
-        .ok_or(ParsingError::SizeMulColorByteCountOverflows)?;
+/// Recognizes the Netpbm subformat from the magic number, only the two bytes
+/// after the leading whitespace are read, the rest of the file isn't validated
+pub fn detect_format(bytes: &[u8]) -> Option<PpmFormat> {
+    let start = find_index(bytes, 0, |elem| !(elem as char).is_whitespace())?;
+    PpmFormat::from_magic(bytes.get(start..start + 2)?)
+}
 
-    if raw_image_data.len() < limit {
-        return Err(ParsingError::LessThanSizePixelsFoundInFile);
+/// The first image of the file, the other ones are still validated. There is
+/// no `Vec<Image>` counterpart since the orphan rule forbids it, use
+/// parse_ppm_file() to get all the images.
+///
+/// ```
+/// use image_parser::Image;
+///
+/// let bytes = b"P6 1 1 255 \xff\x00\x00";
+/// let image = Image::try_from(&bytes[..]).unwrap();
+/// assert_eq!((image.width(), image.height()), (1, 1));
+/// assert_eq!(image[0].as_bytes()[..3], [255, 0, 0]);
+/// ```
+impl TryFrom<&[u8]> for Image {
+    type Error = ParsingError;
+
+    fn try_from(file_content: &[u8]) -> Result<Self, Self::Error> {
+        Ok(parse_ppm_file(file_content)?.into_iter().next().expect(
+            "parse_ppm_file() returned an empty vec!
+             (This should never happen, there is an error in the lib)",
+        ))
     }
+}
 
-    for i in (5..limit).step_by(SIZE_OF_U16_COLOR) {
-        let r = raw_image_data[i - 4] as u16 | ((raw_image_data[i - 5] as u16) << 8);
-        let g = raw_image_data[i - 2] as u16 | ((raw_image_data[i - 3] as u16) << 8);
-        let b = raw_image_data[i] as u16 | ((raw_image_data[i - 1] as u16) << 8);
-        image_data.push(Pixel {
-            rgba: Rgba {
-                r: convert_u16_maxval_color(r, maxval),
-                g: convert_u16_maxval_color(g, maxval),
-                b: convert_u16_maxval_color(b, maxval),
-                a: DEFAULT_ALPHA_VALUE,
-            },
-        });
-    }
+/// A width or height of 0 is valid, it decodes to an image without any pixel
+/// that keeps the dimensions from the header and reads no raster data.
+///
+/// The pixels get the opaque [`DEFAULT_ALPHA_VALUE`] alpha, as with every
+/// entry point that takes no ParseOptions.
+pub fn parse_ppm_file(file_content: &[u8]) -> Result<Vec<Image>, ParsingError> {
+    parse_ppm_file_with_options(file_content, &ParseOptions::unlimited())
+}
 
-    Ok(limit)
+pub fn parse_ppm_file_with_options(
+    file_content: &[u8],
+    options: &ParseOptions,
+) -> Result<Vec<Image>, ParsingError> {
+    parse_images(file_content, options, Vec::new())
 }
 
-fn convert_u8_maxval_color(color: u8, maxval: u8) -> u8 {
-    ((color as f64) / (maxval as f64) * 255.) as u8
+pub fn parse_ppm_file_with_capacity(
+    file_content: &[u8],
+    capacity_hint: usize,
+) -> Result<Vec<Image>, ParsingError> {
+    parse_images(
+        file_content,
+        &ParseOptions::unlimited(),
+        Vec::with_capacity(capacity_hint),
+    )
 }
 
-fn convert_u16_maxval_color(color: u16, maxval: u16) -> u8 {
-    ((color as f64) / (maxval as f64) * 255.) as u8
+pub fn count_ppm_images(file_content: &[u8]) -> Result<usize, ParsingError> {
+    let mut count = 0;
+    for_each_frame(file_content, false, |frame, header| {
+        let raster_len = raster_len(&header)?;
+        if frame.len() - header.raster_offset < raster_len {
+            return Err(ParsingError::LessThanSizePixelsFoundInFile);
+        }
+        count += 1;
+        Ok(header.raster_offset + raster_len)
+    })?;
+    Ok(count)
 }
 
-fn get_content_start_index(slice: &[u8], skip: usize) -> Option<usize> {
-    let mut skip = find_index(slice, skip, |elem| !(elem as char).is_whitespace())?;
-    while slice[skip] == b'#' {
-        skip = find_index(slice, skip + 1, |elem| elem == b'\n')?;
-        skip = find_index(slice, skip + 1, |elem| !(elem as char).is_whitespace())?;
-    }
-    Some(skip)
+/// Decodes only the frame at index `n`, the rasters of the frames before it are
+/// skipped using the length given by their header. Ok(None) when the file has
+/// `n` frames or less.
+pub fn nth_ppm_image(file_content: &[u8], n: usize) -> Result<Option<Image>, ParsingError> {
+    let mut index = 0;
+    let mut image = None;
+    for_each_frame(file_content, false, |frame, header| {
+        if index == n {
+            image = Some(parse_image(frame, &header, DEFAULT_ALPHA_VALUE)?.1);
+            // Consuming the rest of the file stops the scan
+            return Ok(frame.len());
+        }
+
+        let raster_len = raster_len(&header)?;
+        if frame.len() - header.raster_offset < raster_len {
+            return Err(ParsingError::LessThanSizePixelsFoundInFile);
+        }
+        index += 1;
+        Ok(header.raster_offset + raster_len)
+    })?;
+    Ok(image)
 }
 
-fn get_content_end_index(slice: &[u8], skip: usize) -> Option<usize> {
-    find_index(slice, skip, |elem| {
-        (elem as char).is_whitespace() || elem == b'#'
+/// Decodes the frames one at a time and hands them to `f`, so that only one of
+/// them is in memory. Stops at the first error or once `f` returns
+/// ControlFlow::Break, the frames after it aren't read.
+pub fn for_each_ppm_image(
+    file_content: &[u8],
+    mut f: impl FnMut(Image) -> ControlFlow<()>,
+) -> Result<(), ParsingError> {
+    for_each_frame(file_content, false, |frame, header| {
+        let (bytes_read, image) = parse_image(frame, &header, DEFAULT_ALPHA_VALUE)?;
+        match f(image) {
+            ControlFlow::Continue(()) => Ok(bytes_read),
+            // Consuming the rest of the file stops the scan
+            ControlFlow::Break(()) => Ok(frame.len()),
+        }
     })
 }
 
-fn find_index(slice: &[u8], skip: usize, mut find_op: impl FnMut(u8) -> bool) -> Option<usize> {
-    slice.iter().enumerate().skip(skip).find_map(
-        |(i, elem)| {
-            if find_op(*elem) {
-                Some(i)
-            } else {
-                None
-            }
-        },
-    )
-}
+/// Scans every frame boundary first, then decodes the frames in parallel
+#[cfg(feature = "rayon")]
+pub fn parse_ppm_file_parallel(file_content: &[u8]) -> Result<Vec<Image>, ParsingError> {
+    use rayon::prelude::*;
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    let mut frames = Vec::new();
+    for_each_frame(file_content, false, |frame, header| {
+        let raster_len = raster_len(&header)?;
+        if frame.len() - header.raster_offset < raster_len {
+            return Err(ParsingError::LessThanSizePixelsFoundInFile);
+        }
+        let bytes_read = header.raster_offset + raster_len;
+        frames.push((frame, header));
+        Ok(bytes_read)
+    })?;
+
+    frames
+        .par_iter()
+        .map(|(frame, header)| Ok(parse_image(frame, header, DEFAULT_ALPHA_VALUE)?.1))
+        .collect()
+}
+
+pub fn parse_ppm_file_with_meta(
+    file_content: &[u8],
+) -> Result<Vec<(Image, PpmMeta)>, ParsingError> {
+    let mut images = Vec::new();
+    for_each_frame(file_content, false, |frame, header| {
+        let (bytes_read, image) = parse_image(frame, &header, DEFAULT_ALPHA_VALUE)?;
+        let meta = PpmMeta {
+            magic: header
+                .magic
+                .try_into()
+                .expect("parse_image() only accepts 2 bytes formats"),
+            maxval: header.maxval,
+        };
+        images.push((image, meta));
+        Ok(bytes_read)
+    })?;
+    Ok(images)
+}
+
+/// Same as parse_ppm_file() but also returns the `#` comments of each header,
+/// one String per comment without the leading `#` and the ending newline
+pub fn parse_ppm_file_with_comments(
+    file_content: &[u8],
+) -> Result<Vec<(Image, Vec<String>)>, ParsingError> {
+    let mut images = Vec::new();
+    for_each_frame(file_content, false, |frame, header| {
+        let (bytes_read, image) = parse_image(frame, &header, DEFAULT_ALPHA_VALUE)?;
+        images.push((image, header_comments(&frame[..header.raster_offset])));
+        Ok(bytes_read)
+    })?;
+    Ok(images)
+}
+
+/// Same as parse_ppm_file() but also returns the layout of each header, for
+/// Image::to_p6_with_layout() to write it back as it was
+pub fn parse_ppm_file_with_layout(
+    file_content: &[u8],
+) -> Result<Vec<(Image, HeaderLayout)>, ParsingError> {
+    let mut images = Vec::new();
+    for_each_frame(file_content, false, |frame, header| {
+        let (bytes_read, image) = parse_image(frame, &header, DEFAULT_ALPHA_VALUE)?;
+        images.push((image, header_layout(&frame[..header.raster_offset])));
+        Ok(bytes_read)
+    })?;
+    Ok(images)
+}
+
+/// Decodes the binary (P5) and plain (P2) graymaps of a PGM file. The samples
+/// are scaled to a maxval of 255 since GrayImage holds u8 values, so the
+/// precision of 16-bit files isn't kept, and samples above the maxval are
+/// clamped to it.
+pub fn parse_pgm_file(file_content: &[u8]) -> Result<Vec<GrayImage>, ParsingError> {
+    let mut images = Vec::new();
+    for_each_frame(file_content, false, |frame, header| {
+        let (bytes_read, image) = read_gray_image(frame, &header)?;
+        images.push(image);
+        Ok(bytes_read)
+    })?;
+    Ok(images)
+}
+
+fn read_gray_image(frame: &[u8], header: &Header) -> Result<(usize, GrayImage), ParsingError> {
+    let raster = &frame[header.raster_offset..];
+    let scale = |sample: u16| match u8::try_from(header.maxval) {
+        Ok(maxval) => convert_u8_maxval_color(sample.min(maxval as u16) as u8, maxval),
+        Err(_) => convert_u16_maxval_color(sample, header.maxval),
+    };
+
+    let mut data = Vec::new();
+    let bytes_read = match header.format {
+        Some(PpmFormat::P5) => {
+            let sample_len = if header.maxval < 256 { 1 } else { 2 };
+            let raster_len = header
+                .size
+                .checked_mul(sample_len)
+                .ok_or(ParsingError::SizeMulColorByteCountOverflows)?;
+            let raster = raster
+                .get(..raster_len)
+                .ok_or(ParsingError::LessThanSizePixelsFoundInFile)?;
+            data.try_reserve_exact(header.size)
+                .map_err(ParsingError::FailedToAllocateImageData)?;
+            data.extend(raster.chunks_exact(sample_len).map(|sample| match *sample {
+                [gray] => scale(gray as u16),
+                [high, low] => scale(u16::from_be_bytes([high, low])),
+                _ => unreachable!("samples are 1 or 2 bytes long"),
+            }));
+            raster_len
+        }
+        Some(PpmFormat::P2) => {
+            // Every sample takes at least one byte
+            if raster.len() < header.size {
+                return Err(ParsingError::LessThanSizePixelsFoundInFile);
+            }
+            data.try_reserve_exact(header.size)
+                .map_err(ParsingError::FailedToAllocateImageData)?;
+            let mut end = 0;
+            for _ in 0..header.size {
+                let start = get_content_start_index(raster, end)
+                    .ok_or(ParsingError::LessThanSizePixelsFoundInFile)?;
+                end = numeric_field_end(raster, start, get_content_end_index, None)?;
+                let sample: u16 = parse_field(
+                    &raster[start..end],
+                    |_| ParsingError::SampleIsNotAU16(invalid_digit_error()),
+                    ParsingError::SampleIsNotAU16,
+                )?;
+                data.push(scale(sample));
+            }
+            end
+        }
+        _ => return Err(ParsingError::FormatNotSupported),
+    };
+
+    let image = GrayImage::new(header.width, header.height, data);
+    Ok((header.raster_offset + bytes_read, image))
+}
+
+/// Keeps the samples as stored in the file instead of rescaling them to u8, this
+/// is the path that preserves the precision of images with a maxval above 255.
+/// Each image carries the maxval its samples are relative to.
+pub fn parse_ppm_file_16(file_content: &[u8]) -> Result<Vec<Image16>, ParsingError> {
+    let mut images = Vec::new();
+    for_each_frame(file_content, false, |frame, header| {
+        let raster_len = raster_len(&header)?;
+        if frame.len() - header.raster_offset < raster_len {
+            return Err(ParsingError::LessThanSizePixelsFoundInFile);
+        }
+        let raster = &frame[header.raster_offset..header.raster_offset + raster_len];
+
+        let mut image_data = Vec::new();
+        image_data
+            .try_reserve_exact(header.size)
+            .map_err(ParsingError::FailedToAllocateImageData)?;
+        if header.maxval < 256 {
+            image_data.extend(raster.chunks_exact(3).map(|color| Rgb16 {
+                r: color[0] as u16,
+                g: color[1] as u16,
+                b: color[2] as u16,
+            }));
+        } else {
+            image_data.extend(raster.chunks_exact(6).map(|color| Rgb16 {
+                r: u16::from_be_bytes([color[0], color[1]]),
+                g: u16::from_be_bytes([color[2], color[3]]),
+                b: u16::from_be_bytes([color[4], color[5]]),
+            }));
+        }
+
+        images.push(Image16::new(
+            header.width,
+            header.height,
+            header.maxval,
+            image_data,
+        ));
+        Ok(header.raster_offset + raster_len)
+    })?;
+    Ok(images)
+}
+
+fn parse_images(
+    file_content: &[u8],
+    options: &ParseOptions,
+    mut images: Vec<Image>,
+) -> Result<Vec<Image>, ParsingError> {
+    let mut total_pixels: usize = 0;
+    for_each_frame(file_content, options.strict, |frame, header| {
+        if images.len() >= options.max_images {
+            return Err(ParsingError::TooManyImages);
+        }
+        if header.width > options.max_width || header.height > options.max_height {
+            return Err(ParsingError::DimensionsExceedLimit);
+        }
+        if header.size > options.max_pixels {
+            return Err(ParsingError::PixelsExceedLimit);
+        }
+        total_pixels = match total_pixels.checked_add(header.size) {
+            Some(total_pixels) if total_pixels <= options.max_total_pixels => total_pixels,
+            _ => {
+                return Err(ParsingError::TotalPixelsExceedLimit {
+                    image_index: images.len(),
+                })
+            }
+        };
+
+        if options.reject_samples_above_maxval {
+            check_samples(&frame[header.raster_offset..], &header)?;
+        }
+        let (bytes_read, image) = parse_image(frame, &header, options.default_alpha)?;
+        images.push(image);
+        Ok(bytes_read)
+    })?;
+
+    Ok(images)
+}
+
+/// A raster too short for the header is left for the decoding to report
+fn check_samples(raster: &[u8], header: &Header) -> Result<(), ParsingError> {
+    let Some(raster) = raster.get(..raster_len(header)?) else {
+        return Ok(());
+    };
+    let exceeds = if header.maxval < 256 {
+        raster.iter().any(|&sample| sample as u16 > header.maxval)
+    } else {
+        raster
+            .chunks_exact(2)
+            .any(|sample| u16::from_be_bytes([sample[0], sample[1]]) > header.maxval)
+    };
+    match exceeds {
+        true => Err(ParsingError::SampleExceedsMaxval),
+        false => Ok(()),
+    }
+}
+
+// Calls `on_frame` with the content starting at each image and its header,
+// `on_frame` returns how many bytes of that content the image spans
+fn for_each_frame<'a>(
+    file_content: &'a [u8],
+    strict: bool,
+    mut on_frame: impl FnMut(&'a [u8], Header<'a>) -> Result<usize, ParsingError>,
+) -> Result<(), ParsingError> {
+    if file_content.is_empty() {
+        return Err(ParsingError::FormatNotFound);
+    }
+
+    let mut cursor = 0;
+    while cursor < file_content.len() {
+        let frame = &file_content[cursor..];
+        let header = match parse_header(frame, &mut PartialHeader::default()) {
+            Ok(header) if strict && cursor > 0 => match raster_len(&header) {
+                Err(ParsingError::FormatNotSupported) => {
+                    return Err(ParsingError::TrailingGarbage { offset: cursor })
+                }
+                _ => header,
+            },
+            Err(_) if strict && cursor > 0 => {
+                return Err(ParsingError::TrailingGarbage { offset: cursor })
+            }
+            res => res?,
+        };
+        let bytes_read = on_frame(frame, header)?;
+
+        match get_content_start_index(file_content, cursor + bytes_read) {
+            Some(index) => cursor = index,
+            None => break,
+        }
+    }
+
+    Ok(())
+}
+
+pub fn parse_ppm_header_partial(file_content: &[u8]) -> PartialHeader<'_> {
+    let mut partial_header = PartialHeader::default();
+    if let Err(err) = parse_header(file_content, &mut partial_header) {
+        partial_header.error = Some(err);
+    }
+    partial_header
+}
+
+fn parse_image(
+    file_content: &[u8],
+    header: &Header,
+    alpha: u8,
+) -> Result<(usize, Image), ParsingError> {
+    let start = header.raster_offset;
+    let (bytes_read, image) = match header.format {
+        Some(PpmFormat::P6) => read_image(
+            &file_content[start..],
+            header.width,
+            header.height,
+            header.size,
+            header.maxval,
+            alpha,
+        )?,
+        _ => return Err(ParsingError::FormatNotSupported),
+    };
+    Ok((start + bytes_read, image))
+}
+
+fn parse_header<'a>(
+    file_content: &'a [u8],
+    partial_header: &mut PartialHeader<'a>,
+) -> Result<Header<'a>, ParsingError> {
+    let mut start = get_content_start_index(file_content, 0).ok_or_else(|| {
+        // Only a comment can hide the rest of a file that isn't all whitespace
+        match find_index(file_content, 0, |elem| !(elem as char).is_whitespace()) {
+            Some(_) => ParsingError::OnlyCommentsNoImage,
+            None => ParsingError::FormatNotFound,
+        }
+    })?;
+    let mut end =
+        get_content_end_index(file_content, start).ok_or(ParsingError::NoWhitespaceAfterFormat)?;
+    let format = &file_content[start..end];
+    partial_header.magic = Some(format);
+
+    start = get_content_start_index(file_content, end).ok_or(ParsingError::WidthNotFound)?;
+    end = numeric_field_end(
+        file_content,
+        start,
+        get_content_end_index,
+        Some(ParsingError::NoWhitespaceAfterWidth),
+    )?;
+    let width: usize = parse_field(
+        &file_content[start..end],
+        ParsingError::WidthIsNotAUtf8String,
+        ParsingError::WidthIsNotAUsize,
+    )?;
+    partial_header.width = Some(width);
+
+    start = get_content_start_index(file_content, end).ok_or(ParsingError::HeightNotFound)?;
+    end = numeric_field_end(
+        file_content,
+        start,
+        get_content_end_index,
+        Some(ParsingError::NoWhitespaceAfterHeight),
+    )?;
+    let height: usize = parse_field(
+        &file_content[start..end],
+        ParsingError::HeightIsNotAUtf8String,
+        ParsingError::HeightIsNotAUsize,
+    )?;
+    partial_header.height = Some(height);
+
+    let size = width
+        .checked_mul(height)
+        .ok_or(ParsingError::WidthMulHeightOverflowsUsize)?;
+
+    start = get_content_start_index(file_content, end).ok_or(ParsingError::MaxvalNotFound)?;
+    end = numeric_field_end(
+        file_content,
+        start,
+        |slice, start| find_index(slice, start, |elem| (elem as char).is_whitespace()),
+        None,
+    )?;
+    let maxval: u16 = parse_field(
+        &file_content[start..end],
+        ParsingError::MaxvalIsNotAUtf8String,
+        ParsingError::MaxvalIsNotAU16,
+    )?;
+    if maxval == 0 {
+        return Err(ParsingError::MaxvalCantBe0);
+    }
+    partial_header.maxval = Some(maxval);
+
+    Ok(Header {
+        magic: format,
+        format: PpmFormat::from_magic(format),
+        width,
+        height,
+        size,
+        maxval,
+        raster_offset: (end + 1).min(file_content.len()),
+    })
+}
+
+/// The fields of a header that parse_header() accepted are where it found them
+fn header_layout(header: &[u8]) -> HeaderLayout {
+    let mut separators: [Vec<u8>; 5] = Default::default();
+    let mut end = 0;
+    for (index, separator) in separators[..4].iter_mut().enumerate() {
+        let start = get_content_start_index(header, end).unwrap_or(header.len());
+        *separator = header[end..start].to_vec();
+        end = if index < 3 {
+            get_content_end_index(header, start)
+        } else {
+            find_index(header, start, |elem| (elem as char).is_whitespace())
+        }
+        .unwrap_or(header.len());
+    }
+    separators[4] = header[end..].to_vec();
+    HeaderLayout { separators }
+}
+
+fn header_comments(header: &[u8]) -> Vec<String> {
+    // The fields end at a '#', so every '#' of a valid header starts a comment
+    // that parse_header() already checked is closed by a '\n'
+    let mut comments = Vec::new();
+    let mut rest = header;
+    while let Some(start) = rest.iter().position(|&byte| byte == b'#') {
+        let comment = &rest[start + 1..];
+        let end = comment
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .unwrap_or(comment.len());
+        comments.push(String::from_utf8_lossy(&comment[..end]).to_string());
+        rest = &comment[end..];
+    }
+    comments
+}
+
+/// Only looks for the end of the field in its first MAX_NUMERIC_FIELD_LEN + 1
+/// bytes, so an adversarial header can't make the parser scan a huge field.
+/// Without a `no_whitespace_after` error, the end of the file ends the field.
+fn numeric_field_end(
+    file_content: &[u8],
+    start: usize,
+    find_end: impl Fn(&[u8], usize) -> Option<usize>,
+    no_whitespace_after: Option<ParsingError>,
+) -> Result<usize, ParsingError> {
+    let limit = file_content
+        .len()
+        .min(start.saturating_add(MAX_NUMERIC_FIELD_LEN + 1));
+    match find_end(&file_content[..limit], start) {
+        Some(end) => Ok(end),
+        None if limit < file_content.len() => Err(ParsingError::NumericFieldTooLong),
+        None => no_whitespace_after.map_or(Ok(limit), Err),
+    }
+}
+
+fn parse_field<T: str::FromStr<Err = ParseIntError>>(
+    field: &[u8],
+    not_utf8: fn(Utf8Error) -> ParsingError,
+    not_a_number: fn(ParseIntError) -> ParsingError,
+) -> Result<T, ParsingError> {
+    let field = str::from_utf8(field).map_err(not_utf8)?;
+    // parse() accepts a leading '+' but the format only allows digits
+    if !field.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(not_a_number(invalid_digit_error()));
+    }
+    field.parse::<T>().map_err(not_a_number)
+}
+
+/// ParseIntError can't be built directly, a lone sign is always an InvalidDigit
+fn invalid_digit_error() -> ParseIntError {
+    "+".parse::<u8>().unwrap_err()
+}
+
+fn raster_len(header: &Header) -> Result<usize, ParsingError> {
+    let bytes_per_pixel = match header.format {
+        Some(PpmFormat::P6) if header.maxval < 256 => 3,
+        Some(PpmFormat::P6) => 6,
+        _ => return Err(ParsingError::FormatNotSupported),
+    };
+    header
+        .size
+        .checked_mul(bytes_per_pixel)
+        .ok_or(ParsingError::SizeMulColorByteCountOverflows)
+}
+
+fn read_image(
+    raw_image_data: &[u8],
+    width: usize,
+    height: usize,
+    size: usize,
+    maxval: u16,
+    alpha: u8,
+) -> Result<(usize, Image), ParsingError> {
+    if size == 0 {
+        return Ok((0, Image::new(width, height, [])));
+    }
+
+    let mut image_data = Vec::<Pixel>::new();
+    let bytes_read = read_pixels(raw_image_data, size, maxval, alpha, &mut image_data)?;
+    Ok((bytes_read, Image::new(width, height, image_data)))
+}
+
+/// Appends the `size` pixels of the raster to `image_data`, with `alpha` as
+/// their alpha
+fn read_pixels(
+    raw_image_data: &[u8],
+    size: usize,
+    maxval: u16,
+    alpha: u8,
+    image_data: &mut Vec<Pixel>,
+) -> Result<usize, ParsingError> {
+    // A header can announce far more pixels than the file holds, checking the
+    // raster length first keeps it from forcing a huge reservation, which
+    // could succeed with overcommit and only fail once the pixels are written
+    let color_len = if maxval < 256 { 3 } else { 6 };
+    let raster_len = size
+        .checked_mul(color_len)
+        .ok_or(ParsingError::SizeMulColorByteCountOverflows)?;
+    check_fits_platform(size)?;
+    if raw_image_data.len() < raster_len {
+        return Err(ParsingError::LessThanSizePixelsFoundInFile);
+    }
+
+    image_data
+        .try_reserve_exact(size)
+        .map_err(ParsingError::FailedToAllocateImageData)?;
+
+    if maxval < 256 {
+        read_image_from_u8_maxval(raw_image_data, size, maxval as u8, alpha, image_data)
+    } else {
+        read_image_from_u16_maxval(raw_image_data, size, maxval, alpha, image_data)
+    }
+}
+
+/// No allocation, and so no slice, can be larger than isize::MAX bytes
+fn check_fits_platform(size: usize) -> Result<(), ParsingError> {
+    match size.checked_mul(size_of::<Pixel>()) {
+        Some(bytes) if bytes <= isize::MAX as usize => Ok(()),
+        _ => Err(ParsingError::ImageTooLargeForPlatform),
+    }
+}
+
+fn read_image_from_u8_maxval(
+    raw_image_data: &[u8],
+    size: usize,
+    maxval: u8,
+    alpha: u8,
+    image_data: &mut Vec<Pixel>,
+) -> Result<usize, ParsingError> {
+    const SIZE_OF_U8_COLOR: usize = 3;
+    let limit = size
+        .checked_mul(SIZE_OF_U8_COLOR)
+        .ok_or(ParsingError::SizeMulColorByteCountOverflows)?;
+
+    if raw_image_data.len() < limit {
+        return Err(ParsingError::LessThanSizePixelsFoundInFile);
+    }
+
+    #[allow(unused_mut)]
+    let mut start = 0;
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if maxval == 255 && std::is_x86_feature_detected!("ssse3") {
+        // SAFETY: SSSE3 support was just checked, and the try_reserve_exact(size)
+        // of read_pixels(), the only caller, left room for the size pixels of
+        // raw_image_data[..limit] in image_data
+        start = unsafe { simd::widen_rgb_to_rgba(&raw_image_data[..limit], image_data, alpha) };
+    }
+
+    // extend() from an exact size iterator writes straight into the reserved
+    // buffer, without the per pixel capacity check of push()
+    image_data.extend(
+        raw_image_data[start..limit]
+            .chunks_exact(SIZE_OF_U8_COLOR)
+            .map(|color| u8_color_to_pixel(color, maxval, alpha)),
+    );
+
+    Ok(limit)
+}
+
+fn read_image_from_u16_maxval(
+    raw_image_data: &[u8],
+    size: usize,
+    maxval: u16,
+    alpha: u8,
+    image_data: &mut Vec<Pixel>,
+) -> Result<usize, ParsingError> {
+    const SIZE_OF_U16_COLOR: usize = 6;
+    let limit = size
+        .checked_mul(SIZE_OF_U16_COLOR)
+        .ok_or(ParsingError::SizeMulColorByteCountOverflows)?;
+
+    if raw_image_data.len() < limit {
+        return Err(ParsingError::LessThanSizePixelsFoundInFile);
+    }
+
+    image_data.extend(
+        raw_image_data[..limit]
+            .chunks_exact(SIZE_OF_U16_COLOR)
+            .map(|color| u16_color_to_pixel(color, maxval, alpha)),
+    );
+
+    Ok(limit)
+}
+
+fn u8_color_to_pixel(color: &[u8], maxval: u8, alpha: u8) -> Pixel {
+    Pixel {
+        rgba: Rgba {
+            r: convert_u8_maxval_color(color[0], maxval),
+            g: convert_u8_maxval_color(color[1], maxval),
+            b: convert_u8_maxval_color(color[2], maxval),
+            a: alpha,
+        },
+    }
+}
+
+/// The samples are big endian, the most significant byte comes first
+fn u16_color_to_pixel(color: &[u8], maxval: u16, alpha: u8) -> Pixel {
+    let r = color[1] as u16 | ((color[0] as u16) << 8);
+    let g = color[3] as u16 | ((color[2] as u16) << 8);
+    let b = color[5] as u16 | ((color[4] as u16) << 8);
+    Pixel {
+        rgba: Rgba {
+            r: convert_u16_maxval_color(r, maxval),
+            g: convert_u16_maxval_color(g, maxval),
+            b: convert_u16_maxval_color(b, maxval),
+            a: alpha,
+        },
+    }
+}
+
+// Samples larger than the maxval are clamped to it
+fn convert_u8_maxval_color(color: u8, maxval: u8) -> u8 {
+    ((color.min(maxval) as f64) / (maxval as f64) * 255.) as u8
+}
+
+fn convert_u16_maxval_color(color: u16, maxval: u16) -> u8 {
+    ((color.min(maxval) as f64) / (maxval as f64) * 255.) as u8
+}
+
+fn get_content_start_index(slice: &[u8], skip: usize) -> Option<usize> {
+    let mut skip = find_index(slice, skip, |elem| !(elem as char).is_whitespace())?;
+    while slice[skip] == b'#' {
+        skip = find_index(slice, skip + 1, |elem| elem == b'\n')?;
+        skip = find_index(slice, skip + 1, |elem| !(elem as char).is_whitespace())?;
+    }
+    Some(skip)
+}
+
+fn get_content_end_index(slice: &[u8], skip: usize) -> Option<usize> {
+    find_index(slice, skip, |elem| {
+        (elem as char).is_whitespace() || elem == b'#'
+    })
+}
+
+fn find_index(slice: &[u8], skip: usize, mut find_op: impl FnMut(u8) -> bool) -> Option<usize> {
+    slice.iter().enumerate().skip(skip).find_map(
+        |(i, elem)| {
+            if find_op(*elem) {
+                Some(i)
+            } else {
+                None
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::num::IntErrorKind;
 
     #[test]
     fn single_image() {
@@ -316,10 +1605,9 @@ mod test {
         let mut file: Vec<u8> = Vec::new();
         file.extend_from_slice(b"P6 4 4 255 ");
         push_pixel_data(&mut file, &data);
-        let expected = Image::new(4, 4, data);
         let res = parse_ppm_file(&file).unwrap();
         assert_eq!(res.len(), 1);
-        assert_eq!(expected, res[0])
+        assert_eq!(res[0], (4, 4, &data[..]))
     }
 
     #[test]
@@ -376,6 +1664,14 @@ mod test {
             ParsingError::FormatNotFound => {}
             _ => panic!("Expected ImageFromPpmFileError::FormatNotFound found {res}"),
         };
+
+        for file in [&b"# hello\n"[..], b" # hello\n\n#world", b"#"] {
+            let res = parse_ppm_file(file).unwrap_err();
+            match res {
+                ParsingError::OnlyCommentsNoImage => {}
+                _ => panic!("Expected ParsingError::OnlyCommentsNoImage found {res}"),
+            };
+        }
     }
 
     #[test]
@@ -425,7 +1721,7 @@ mod test {
             _ => panic!("Expected ImageFromPpmFileError::WidthIsNotAUsize found {res}"),
         };
 
-        let res = parse_ppm_file(b"P6 99999999999999999999999999999 2 4 ").unwrap_err();
+        let res = parse_ppm_file(b"P6 99999999999999999999 2 4 ").unwrap_err();
         match res {
             ParsingError::WidthIsNotAUsize(_) => {}
             _ => panic!("Expected ImageFromPpmFileError::WidthIsNotAUsize found {res}"),
@@ -470,7 +1766,7 @@ mod test {
             _ => panic!("Expected ImageFromPpmFileError::HeightIsNotAUsize found {res}"),
         };
 
-        let res = parse_ppm_file(b"P6 5 99999999999999999999999999999 255 ").unwrap_err();
+        let res = parse_ppm_file(b"P6 5 99999999999999999999 255 ").unwrap_err();
         match res {
             ParsingError::HeightIsNotAUsize(_) => {}
             _ => panic!("Expected ImageFromPpmFileError::HeightIsNotAUsize found {res}"),
@@ -499,22 +1795,70 @@ mod test {
             }
         };
 
-        // Not sure how to test SizeMulColorByteCountOverflows since FailedToAllocateImageData
-        // happens before
-    }
-
-    #[test]
-    fn allocation_failure() {
         let res = parse_ppm_file(format!("P6 {} 1 256 ", usize::MAX).as_bytes()).unwrap_err();
         match res {
-            ParsingError::FailedToAllocateImageData(_) => {}
-            _ => panic!("Expected ImageFromPpmFileError::FailedToAllocateImageData found {res}"),
+            ParsingError::SizeMulColorByteCountOverflows => {}
+            _ => {
+                panic!("Expected ImageFromPpmFileError::SizeMulColorByteCountOverflows found {res}")
+            }
         };
     }
 
     #[test]
-    fn bad_maxval() {
-        let res = parse_ppm_file(b"P6 4 2 2f55 ").unwrap_err();
+    fn u16_raster_overflow_fails_before_allocating() {
+        // size * 3 fits in a usize but the 6 bytes of a 16-bit pixel overflow
+        let header = format!("P6 {} 1 65535 ", usize::MAX / 6 + 1);
+        let file = header.as_bytes();
+        #[allow(unused_mut)]
+        let mut results = vec![
+            parse_ppm_file(file).map(drop),
+            parse_ppm_file_16(file).map(drop),
+            parse_ppm_into(file, &mut Vec::new()).map(drop),
+            decode_ppm_to_rgb(file).map(drop),
+        ];
+        #[cfg(feature = "std")]
+        results.push(Image::from_ppm_reader_streaming(file).map(drop));
+
+        for res in results {
+            let res = res.unwrap_err();
+            match res {
+                ParsingError::SizeMulColorByteCountOverflows => {}
+                _ => panic!("Expected ParsingError::SizeMulColorByteCountOverflows found {res}"),
+            };
+        }
+    }
+
+    #[test]
+    fn short_raster_fails_before_allocating() {
+        let res = parse_ppm_file(b"P6 100000 100000 255 abc").unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+
+        let res = parse_ppm_into(b"P6 100000 100000 65535 abc", &mut Vec::new()).unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn allocation_failure() {
+        // A stream can't be checked for its length, so the reservation is tried
+        let width = isize::MAX as usize / size_of::<Pixel>();
+        let header = format!("P6 {width} 1 65535 ");
+        let res = Image::from_ppm_reader_streaming(header.as_bytes()).unwrap_err();
+        match res {
+            ParsingError::FailedToAllocateImageData(_) => {}
+            _ => panic!("Expected ImageFromPpmFileError::FailedToAllocateImageData found {res}"),
+        };
+    }
+
+    #[test]
+    fn bad_maxval() {
+        let res = parse_ppm_file(b"P6 4 2 2f55 ").unwrap_err();
         match res {
             ParsingError::MaxvalIsNotAU16(_) => {}
             _ => panic!("Expected ImageFromPpmFileError::MaxvalIsNotAU16 found {res}"),
@@ -546,8 +1890,10 @@ mod test {
 
         let res = parse_ppm_file(b"P6 4 2 255").unwrap_err();
         match res {
-            ParsingError::NoWhitespaceAfterMaxval => {}
-            _ => panic!("Expected ImageFromPpmFileError::NoWhitespaceAfterMaxval found {res}"),
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => {
+                panic!("Expected ImageFromPpmFileError::LessThanSizePixelsFoundInFile found {res}")
+            }
         };
 
         let res = parse_ppm_file(b"P6 4 2 ").unwrap_err();
@@ -582,6 +1928,959 @@ mod test {
         };
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn error_source_chain() {
+        let parsing_error = parse_ppm_file(b"P6 4f3 5 255 ").unwrap_err();
+        let source = parsing_error
+            .source()
+            .expect("WidthIsNotAUsize should expose its ParseIntError");
+        assert!(source.downcast_ref::<ParseIntError>().is_some());
+
+        let err = ImagesFromPpmFileError {
+            parsing_error,
+            file_name: "test.ppm".to_string(),
+        };
+        let source = err.source().unwrap();
+        match source.downcast_ref::<ParsingError>() {
+            Some(ParsingError::WidthIsNotAUsize(_)) => {}
+            _ => panic!("Expected ParsingError::WidthIsNotAUsize found {source}"),
+        };
+        assert!(source.source().is_some());
+
+        assert!(parse_ppm_file(b"").unwrap_err().source().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn error_display() {
+        let err = ImagesFromPpmFileError {
+            parsing_error: parse_ppm_file(b"P6 4 2 0 ").unwrap_err(),
+            file_name: "test.ppm".to_string(),
+        };
+        assert_eq!(err.to_string(), "test.ppm: PPM maxval can't be 0");
+
+        let err = parse_ppm_file(b"P6 -4 2 255 ").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "PPM width field is not a valid unsigned integer: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn partial_header() {
+        let header = parse_ppm_header_partial(b"P6 4 f 255 ");
+        assert_eq!(header.magic, Some(&b"P6"[..]));
+        assert_eq!(header.width, Some(4));
+        assert_eq!(header.height, None);
+        assert_eq!(header.maxval, None);
+        match header.error {
+            Some(ParsingError::HeightIsNotAUsize(_)) => {}
+            _ => panic!(
+                "Expected ParsingError::HeightIsNotAUsize found {:?}",
+                header.error
+            ),
+        };
+
+        let header = parse_ppm_header_partial(b"P6 4 2 0 ");
+        assert_eq!(header.height, Some(2));
+        assert_eq!(header.maxval, None);
+        match header.error {
+            Some(ParsingError::MaxvalCantBe0) => {}
+            _ => panic!(
+                "Expected ParsingError::MaxvalCantBe0 found {:?}",
+                header.error
+            ),
+        };
+
+        let header = parse_ppm_header_partial(b"P6 4 2 255 ");
+        assert_eq!(header.magic, Some(&b"P6"[..]));
+        assert_eq!(header.width, Some(4));
+        assert_eq!(header.height, Some(2));
+        assert_eq!(header.maxval, Some(255));
+        assert!(header.error.is_none());
+    }
+
+    #[test]
+    fn image_limits() {
+        let mut file: Vec<u8> = Vec::new();
+        for _ in 0..3 {
+            file.extend_from_slice(b"P6 2 2 255 ");
+            file.extend_from_slice(&[0; 2 * 2 * 3]);
+        }
+
+        let options = ParseOptions {
+            max_images: 3,
+            max_pixels: 4,
+            max_total_pixels: 12,
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            parse_ppm_file_with_options(&file, &options).unwrap().len(),
+            3
+        );
+
+        let res = parse_ppm_file_with_options(
+            &file,
+            &ParseOptions {
+                max_total_pixels: 10,
+                ..options
+            },
+        )
+        .unwrap_err();
+        match res {
+            ParsingError::TotalPixelsExceedLimit { image_index: 2 } => {}
+            _ => panic!("Expected ParsingError::TotalPixelsExceedLimit at image 2 found {res}"),
+        };
+
+        let res = parse_ppm_file_with_options(
+            &file,
+            &ParseOptions {
+                max_images: 2,
+                ..options
+            },
+        )
+        .unwrap_err();
+        match res {
+            ParsingError::TooManyImages => {}
+            _ => panic!("Expected ParsingError::TooManyImages found {res}"),
+        };
+
+        let res = parse_ppm_file_with_options(
+            &file,
+            &ParseOptions {
+                max_pixels: 3,
+                ..options
+            },
+        )
+        .unwrap_err();
+        match res {
+            ParsingError::PixelsExceedLimit => {}
+            _ => panic!("Expected ParsingError::PixelsExceedLimit found {res}"),
+        };
+    }
+
+    #[test]
+    fn count_images() {
+        let mut file: Vec<u8> = Vec::new();
+        file.extend_from_slice(b"P6 2 1 255 ");
+        file.extend_from_slice(&[0; 2 * 3]);
+        file.extend_from_slice(b"\n# comment\nP6 1 1 65535\n");
+        file.extend_from_slice(&[0; 6]);
+        file.extend_from_slice(b"P6 1 2 1 ");
+        file.extend_from_slice(&[0; 2 * 3]);
+        assert_eq!(count_ppm_images(&file).unwrap(), 3);
+
+        let images = parse_ppm_file_with_capacity(&file, 3).unwrap();
+        assert_eq!(images.len(), 3);
+        assert!(images.capacity() >= 3);
+
+        let res = count_ppm_images(&file[..file.len() - 1]).unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+
+        let res = count_ppm_images(b"P3 1 1 255 ").unwrap_err();
+        match res {
+            ParsingError::FormatNotSupported => {}
+            _ => panic!("Expected ParsingError::FormatNotSupported found {res}"),
+        };
+
+        let res = count_ppm_images(b"").unwrap_err();
+        match res {
+            ParsingError::FormatNotFound => {}
+            _ => panic!("Expected ParsingError::FormatNotFound found {res}"),
+        };
+    }
+
+    #[test]
+    fn dimension_limits() {
+        let options = ParseOptions::default();
+        let res = parse_ppm_file_with_options(b"P6 65535 65535 65535 ", &options).unwrap_err();
+        match res {
+            ParsingError::PixelsExceedLimit => {}
+            _ => panic!("Expected ParsingError::PixelsExceedLimit found {res}"),
+        };
+
+        let res =
+            parse_ppm_file_with_options(format!("P6 {} 1 255 ", usize::MAX).as_bytes(), &options)
+                .unwrap_err();
+        match res {
+            ParsingError::DimensionsExceedLimit => {}
+            _ => panic!("Expected ParsingError::DimensionsExceedLimit found {res}"),
+        };
+
+        let options = ParseOptions {
+            max_width: 2,
+            max_height: 1,
+            ..ParseOptions::default()
+        };
+        let res = parse_ppm_file_with_options(b"P6 2 2 255 ", &options).unwrap_err();
+        match res {
+            ParsingError::DimensionsExceedLimit => {}
+            _ => panic!("Expected ParsingError::DimensionsExceedLimit found {res}"),
+        };
+        let res = parse_ppm_file_with_options(b"P6 2 1 255 abcdef", &options).unwrap();
+        assert_eq!(res.len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn parallel_parsing() {
+        let mut file: Vec<u8> = Vec::new();
+        for i in 0..20u8 {
+            file.extend_from_slice(format!("P6 {} 1 255\n", i + 1).as_bytes());
+            file.extend((0..(i as usize + 1) * 3).map(|value| value as u8 ^ i));
+        }
+
+        let expected = parse_ppm_file(&file).unwrap();
+        assert_eq!(parse_ppm_file_parallel(&file).unwrap(), expected);
+
+        let res = parse_ppm_file_parallel(&file[..file.len() - 1]).unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+    }
+
+    #[test]
+    fn maxval_255_matches_raw_bytes() {
+        for pixel_count in [0, 1, 4, 5, 6, 7, 33] {
+            let raw: Vec<u8> = (0..pixel_count * 3).map(|i| (i * 37 % 256) as u8).collect();
+            let mut file = format!("P6 {pixel_count} 1 255 ").into_bytes();
+            file.extend_from_slice(&raw);
+
+            let expected =
+                Image::from_raw_bytes(pixel_count, 1, crate::Channels::Rgb, &raw).unwrap();
+            assert_eq!(parse_ppm_file(&file).unwrap(), [expected]);
+        }
+    }
+
+    #[test]
+    fn samples_above_maxval() {
+        let reject = ParseOptions {
+            reject_samples_above_maxval: true,
+            ..ParseOptions::default()
+        };
+        let mut u16_file = b"P6 1 1 1000 ".to_vec();
+        u16_file.extend([0x03, 0xe8, 0x03, 0xe9, 0xff, 0xff]);
+
+        for (file, expected) in [
+            (&b"P6 1 1 100 \x64\x65\xff"[..], [255, 255, 255]),
+            (&b"P6 1 1 100 \x00\x32\x65"[..], [0, 127, 255]),
+            (&u16_file, [255, 255, 255]),
+        ] {
+            let res = parse_ppm_file_with_options(file, &ParseOptions::default()).unwrap();
+            assert_eq!(res[0][0].as_bytes()[..3], expected);
+
+            let res = parse_ppm_file_with_options(file, &reject).unwrap_err();
+            match res {
+                ParsingError::SampleExceedsMaxval => {}
+                _ => panic!("Expected ParsingError::SampleExceedsMaxval found {res}"),
+            };
+        }
+
+        let res = parse_ppm_file_with_options(b"P6 1 1 100 \x00\x32\x64", &reject).unwrap();
+        assert_eq!(res[0][0].as_bytes()[..3], [0, 127, 255]);
+        let res = parse_ppm_file_with_options(b"P6 1 1 100 \x00", &reject).unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+    }
+
+    #[test]
+    fn strict_trailing_garbage() {
+        let strict = ParseOptions {
+            strict: true,
+            ..ParseOptions::default()
+        };
+
+        let res = parse_ppm_file_with_options(b"P6 1 1 255 rgb \n# comment\n  ", &strict).unwrap();
+        assert_eq!(res.len(), 1);
+
+        let res = parse_ppm_file_with_options(b"P6 1 1 255 rgb\ngarbage", &strict).unwrap_err();
+        match res {
+            ParsingError::TrailingGarbage { offset: 15 } => {}
+            _ => panic!("Expected ParsingError::TrailingGarbage at 15 found {res}"),
+        };
+
+        let res = parse_ppm_file_with_options(b"P6 1 1 255 rgbxx 1 1 255 ", &strict).unwrap_err();
+        match res {
+            ParsingError::TrailingGarbage { offset: 14 } => {}
+            _ => panic!("Expected ParsingError::TrailingGarbage at 14 found {res}"),
+        };
+
+        let res =
+            parse_ppm_file_with_options(b"P6 1 1 255 rgbxx 1 1 255 ", &ParseOptions::default())
+                .unwrap_err();
+        match res {
+            ParsingError::FormatNotSupported => {}
+            _ => panic!("Expected ParsingError::FormatNotSupported found {res}"),
+        };
+
+        let res = parse_ppm_file_with_options(b"xx 1 1 255 ", &strict).unwrap_err();
+        match res {
+            ParsingError::FormatNotSupported => {}
+            _ => panic!("Expected ParsingError::FormatNotSupported found {res}"),
+        };
+    }
+
+    #[test]
+    fn maxval_boundaries() {
+        // (maxval, raster of one pixel, expected rgb)
+        let cases: [(&str, &[u8], [u8; 3]); 6] = [
+            ("1", &[0, 1, 1], [0, 255, 255]),
+            ("255", &[0, 128, 255], [0, 128, 255]),
+            ("00255", &[0, 128, 255], [0, 128, 255]),
+            ("256", &[0x00, 0x00, 0x00, 0x80, 0x01, 0x00], [0, 127, 255]),
+            (
+                "65535",
+                &[0x00, 0x00, 0x80, 0x00, 0xff, 0xff],
+                [0, 127, 255],
+            ),
+            (
+                "65535",
+                &[0x00, 0xff, 0x7f, 0xff, 0xff, 0xfe],
+                [0, 127, 254],
+            ),
+        ];
+
+        for (maxval, raster, [r, g, b]) in cases {
+            // A second image right after the raster only parses if exactly
+            // raster.len() bytes were consumed by the first one
+            let mut file = format!("P6 1 1 {maxval} ").into_bytes();
+            file.extend_from_slice(raster);
+            file.extend_from_slice(format!("P6 1 1 {maxval} ").as_bytes());
+            file.extend_from_slice(raster);
+
+            let res = parse_ppm_file(&file).unwrap();
+            assert_eq!(res.len(), 2, "maxval {maxval}");
+            let expected = Rgba {
+                r,
+                g,
+                b,
+                a: DEFAULT_ALPHA_VALUE,
+            };
+            assert_eq!(res[0][0].rgba(), expected, "maxval {maxval}");
+            assert_eq!(res[1][0].rgba(), expected, "maxval {maxval}");
+        }
+    }
+
+    #[test]
+    fn u16_samples_are_big_endian() {
+        let mut file = b"P6 2 2 65535\n".to_vec();
+        file.extend_from_slice(&[
+            0x12, 0x34, 0xff, 0x00, 0x00, 0xff, // 0x1234, 0xff00, 0x00ff
+            0xab, 0xcd, 0xff, 0xff, 0x00, 0x00, // 0xabcd, 0xffff, 0x0000
+            0x80, 0x00, 0x01, 0x01, 0xff, 0xfe, // 0x8000, 0x0101, 0xfffe
+            0x00, 0x01, 0x7f, 0xff, 0xfe, 0x00, // 0x0001, 0x7fff, 0xfe00
+        ]);
+
+        let res = parse_ppm_file(&file).unwrap();
+        let rgb: Vec<[u8; 3]> = res[0]
+            .iter()
+            .map(|pixel| [pixel.r(), pixel.g(), pixel.b()])
+            .collect();
+        assert_eq!(
+            rgb,
+            [[18, 254, 0], [171, 255, 0], [127, 1, 254], [0, 127, 253]]
+        );
+    }
+
+    #[test]
+    fn unscaled_samples() {
+        let mut file = b"P6 2 1 1000 ".to_vec();
+        file.extend_from_slice(&[0x03, 0xe8, 0x00, 0x01, 0x01, 0xf4, 0, 0, 0, 2, 0, 3]);
+        file.extend_from_slice(b"P6 1 1 200 ");
+        file.extend_from_slice(&[1, 2, 200]);
+
+        let res = parse_ppm_file_16(&file).unwrap();
+        let expected = [
+            Image16::new(
+                2,
+                1,
+                1000,
+                [
+                    Rgb16 {
+                        r: 1000,
+                        g: 1,
+                        b: 500,
+                    },
+                    Rgb16 { r: 0, g: 2, b: 3 },
+                ],
+            ),
+            Image16::new(1, 1, 200, [Rgb16 { r: 1, g: 2, b: 200 }]),
+        ];
+        assert_eq!(res, expected);
+
+        let res = parse_ppm_file_16(&file[..file.len() - 1]).unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+    }
+
+    #[test]
+    fn meta() {
+        let res = parse_ppm_file_with_meta(b"P6 1 1 255 rgb P6 1 1 1000 rrggbb").unwrap();
+        assert_eq!(res.len(), 2);
+        assert_eq!(
+            res[0].1,
+            PpmMeta {
+                magic: *b"P6",
+                maxval: 255
+            }
+        );
+        assert_eq!(
+            res[1].1,
+            PpmMeta {
+                magic: *b"P6",
+                maxval: 1000
+            }
+        );
+        assert_eq!(res[1].0, parse_ppm_file(b"P6 1 1 1000 rrggbb").unwrap()[0]);
+    }
+
+    #[test]
+    fn zero_dimensions() {
+        let res = parse_ppm_file(b"P6 0 4 255 ").unwrap();
+        assert_eq!(res, [Image::new(0, 4, [])]);
+
+        let res = parse_ppm_file(b"P6 4 0 65535 P6 1 1 255 rgb").unwrap();
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0], Image::new(4, 0, []));
+        assert_eq!(res[0].len(), 0);
+        assert_eq!(res[1].width(), 1);
+
+        // The end of the file also ends the maxval
+        let res = parse_ppm_file(b"P6 0 0 255").unwrap();
+        assert_eq!(res, [Image::new(0, 0, [])]);
+        let header = parse_ppm_header_partial(b"P6 2 1 65535");
+        assert!(header.error.is_none());
+        assert_eq!(header.maxval, Some(65535));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn streaming_matches_in_memory_parsing() {
+        use std::io::{BufReader, Cursor};
+
+        let mut file = b"P6\n# a comment\n3 # another\n2\n255\n".to_vec();
+        file.extend((0..18).map(|i| i * 13));
+        let expected = parse_ppm_file(&file).unwrap().remove(0);
+        for capacity in [1, 2, 4, 5, 7, 64] {
+            let reader = BufReader::with_capacity(capacity, Cursor::new(&file));
+            assert_eq!(Image::from_ppm_reader_streaming(reader).unwrap(), expected);
+        }
+
+        let mut file = b"P6 2 1 65535 ".to_vec();
+        file.extend([0x12, 0x34, 0xff, 0xff, 0x80, 0x00, 0, 0, 0, 1, 0xab, 0xcd]);
+        let expected = parse_ppm_file(&file).unwrap().remove(0);
+        for capacity in [1, 4, 5, 64] {
+            let reader = BufReader::with_capacity(capacity, Cursor::new(&file));
+            assert_eq!(Image::from_ppm_reader_streaming(reader).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn streaming_errors() {
+        use std::io::{BufReader, Cursor};
+
+        let res = Image::from_ppm_reader_streaming(BufReader::with_capacity(
+            4,
+            Cursor::new(b"P6 2 2 255 abcdefghi"),
+        ))
+        .unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+
+        let res = Image::from_ppm_reader_streaming(Cursor::new(b"P6 2 # 2 255 ")).unwrap_err();
+        match res {
+            ParsingError::HeightNotFound => {}
+            _ => panic!("Expected ParsingError::HeightNotFound found {res}"),
+        };
+
+        let res = Image::from_ppm_reader_streaming(Cursor::new(b"P3 1 1 255 0 0 0")).unwrap_err();
+        match res {
+            ParsingError::FormatNotSupported => {}
+            _ => panic!("Expected ParsingError::FormatNotSupported found {res}"),
+        };
+
+        let image = Image::from_ppm_reader_streaming(Cursor::new(b"P6 0 3 255 ")).unwrap();
+        assert_eq!((image.width(), image.height(), image.len()), (0, 3, 0));
+        let image = Image::from_ppm_reader_streaming(&b"P6 0 0 255"[..]).unwrap();
+        assert_eq!((image.width(), image.height()), (0, 0));
+    }
+
+    #[test]
+    fn header_comments_are_collected() {
+        let mut file = b"P6\n# author: someone\n2 1\n#created 2024-01-01\r\n255\n".to_vec();
+        file.extend([1, 2, 3, 4, 5, 6]);
+        file.extend(b"P6 1 1 255 abc");
+        let res = parse_ppm_file_with_comments(&file).unwrap();
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].0, parse_ppm_file(&file).unwrap()[0]);
+        assert_eq!(res[0].1, [" author: someone", "created 2024-01-01\r"]);
+        assert!(res[1].1.is_empty());
+    }
+
+    #[test]
+    fn header_layout_round_trip() {
+        let mut tabs = b"P6\t2\t1\t255\t".to_vec();
+        tabs.extend([1, 2, 3, 4, 5, 6]);
+        let mut comments = b" \nP6 # a comment\n2\r\n1\n#\n 255\n".to_vec();
+        comments.extend([7, 8, 9, 10, 11, 12]);
+        let mut canonical = b"P6\n2 1\n255\n".to_vec();
+        canonical.extend([1, 2, 3, 4, 5, 6]);
+
+        for file in [tabs, comments, canonical] {
+            let res = parse_ppm_file_with_layout(&file).unwrap();
+            assert_eq!(res.len(), 1);
+            assert_eq!(res[0].0.to_p6_with_layout(&res[0].1), file);
+        }
+
+        let res = parse_ppm_file_with_layout(b"P6\t1 1 255 abcP6 1\t1 255 def").unwrap();
+        assert_eq!(res[0].1.separators[1], b"\t");
+        assert_eq!(res[1].1.separators[2], b"\t");
+        assert_eq!(res[1].0.to_p6(), b"P6\n1 1\n255\ndef");
+
+        let res = parse_ppm_file_with_layout(b"P6 0 0 255").unwrap();
+        assert!(res[0].1.separators[4].is_empty());
+        assert_eq!(res[0].0.to_p6_with_layout(&res[0].1), b"P6 0 0 255");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn append_images() {
+        let path = std::env::temp_dir().join(format!("append_ppm_{}.ppm", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let first = Image::new(2, 1, [Rgba::RED, Rgba::rgb(1, 2, 3)].map(Pixel::from));
+        let second = Image::new(1, 2, [Rgba::rgb(254, 128, 0), Rgba::WHITE].map(Pixel::from));
+        first.append_ppm(path, 255).unwrap();
+        second.append_ppm(path, 65535).unwrap();
+        let res = Vec::<Image>::try_from(PpmFilePath(path));
+        std::fs::remove_file(path).unwrap();
+
+        let res = res.unwrap();
+        assert_eq!(res.len(), 2);
+        assert!(res[0].eq_ignore_alpha(&first));
+        assert!(res[1].eq_ignore_alpha(&second));
+
+        let res = first.append_ppm(path, 0).unwrap_err();
+        match res {
+            SaveError::MaxvalCantBe0 => {}
+            _ => panic!("Expected SaveError::MaxvalCantBe0 found {res}"),
+        };
+    }
+
+    #[test]
+    fn for_each_image() {
+        let file = b"P6 1 1 255 abcP6 2 1 255 defghiP6 1 1 255 jkl";
+        let mut widths = Vec::new();
+        for_each_ppm_image(file, |image| {
+            widths.push(image.width());
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(widths, [1, 2, 1]);
+
+        let mut count = 0;
+        let broken = b"P6 1 1 255 abcP6 2 1 255 defghiP6 1 1 255";
+        for_each_ppm_image(broken, |_| {
+            count += 1;
+            if count == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .unwrap();
+        assert_eq!(count, 2);
+
+        let res = for_each_ppm_image(broken, |_| ControlFlow::Continue(())).unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+    }
+
+    #[test]
+    fn alpha_from_pgm() {
+        let mut image = parse_ppm_file(b"P6 2 2 255 abcdefghijkl")
+            .unwrap()
+            .remove(0);
+        let colors = image.clone();
+        image
+            .with_alpha_from_pgm(b"P5\n# mask\n2 2\n255\n\x00\x80\xfe\xff")
+            .unwrap();
+        let alphas: Vec<u8> = image.iter().map(|pixel| pixel.a()).collect();
+        assert_eq!(alphas, [0, 128, 254, 255]);
+        assert!(image.eq_ignore_alpha(&colors));
+
+        let mut mask = b"P5 2 2 1000 ".to_vec();
+        mask.extend([0x03, 0xe8, 0, 0, 0x01, 0xf4, 0, 1]);
+        image.with_alpha_from_pgm(&mask).unwrap();
+        let alphas: Vec<u8> = image.iter().map(|pixel| pixel.a()).collect();
+        assert_eq!(alphas, [255, 0, 127, 0]);
+
+        image.with_alpha_from_pgm(b"P2 2 2 3 0 1\n2 3").unwrap();
+        let alphas: Vec<u8> = image.iter().map(|pixel| pixel.a()).collect();
+        assert_eq!(alphas, [0, 85, 170, 255]);
+
+        let res = image.with_alpha_from_pgm(b"P5 2 1 255 ab").unwrap_err();
+        match res {
+            MaskError::DimensionMismatch {
+                image: (2, 2),
+                mask: (2, 1),
+            } => {}
+            _ => panic!("Expected MaskError::DimensionMismatch found {res}"),
+        };
+        let res = image
+            .with_alpha_from_pgm(b"P6 2 2 255 abcdefghijkl")
+            .unwrap_err();
+        match res {
+            MaskError::Parsing(ParsingError::FormatNotSupported) => {}
+            _ => panic!("Expected ParsingError::FormatNotSupported found {res}"),
+        };
+        let res = image.with_alpha_from_pgm(b"P5 2 2 255 abc").unwrap_err();
+        match res {
+            MaskError::Parsing(ParsingError::LessThanSizePixelsFoundInFile) => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+    }
+
+    #[test]
+    fn pgm_files() {
+        let mut file = b"P5\n# CREATOR: GIMP PNM Filter Version 1.1\n3 2\n255\n".to_vec();
+        file.extend([0, 64, 128, 192, 254, 255]);
+        file.extend(b"P2\n# plain\n2 2\n15\n 0 15\n\t7  16\n");
+        file.extend(b"P5 2 1 65535 ");
+        file.extend([0x80, 0x00, 0xff, 0xff]);
+
+        let res = parse_pgm_file(&file).unwrap();
+        assert_eq!(
+            res,
+            [
+                GrayImage::new(3, 2, [0, 64, 128, 192, 254, 255]),
+                GrayImage::new(2, 2, [0, 255, 119, 255]),
+                GrayImage::new(2, 1, [127, 255]),
+            ]
+        );
+
+        let res = parse_pgm_file(b"P5 2 2 255 abc").unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+        let res = parse_pgm_file(b"P2 2 1 255 1 +2").unwrap_err();
+        match res {
+            ParsingError::SampleIsNotAU16(_) => {}
+            _ => panic!("Expected ParsingError::SampleIsNotAU16 found {res}"),
+        };
+        let res = parse_pgm_file(b"P2 2 1 255 1 ").unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+        let res = parse_pgm_file(b"P6 1 1 255 abc").unwrap_err();
+        match res {
+            ParsingError::FormatNotSupported => {}
+            _ => panic!("Expected ParsingError::FormatNotSupported found {res}"),
+        };
+    }
+
+    #[test]
+    fn detect_format() {
+        assert_eq!(super::detect_format(b"P6 4 4 255 "), Some(PpmFormat::P6));
+        assert_eq!(super::detect_format(b"\n \tP1"), Some(PpmFormat::P1));
+        assert_eq!(super::detect_format(b"P7\nWIDTH 4"), Some(PpmFormat::P7));
+        assert_eq!(super::detect_format(b"P5"), Some(PpmFormat::P5));
+        assert_eq!(super::detect_format(b"P8 1 1 1 "), None);
+        assert_eq!(super::detect_format(b"  P"), None);
+        assert_eq!(super::detect_format(b""), None);
+        assert_eq!(super::detect_format(b"\x89PNG"), None);
+    }
+
+    #[test]
+    fn parse_into_reused_buffer() {
+        let mut first = b"P6 2 2 255 ".to_vec();
+        first.extend(1..=12);
+        let mut second = b"P6 1 2 65535 ".to_vec();
+        second.extend([0xff, 0xff, 0, 0, 0x80, 0, 0, 0, 0xff, 0xff, 0, 0]);
+
+        let mut buffer = Vec::new();
+        assert_eq!(parse_ppm_into(&first, &mut buffer).unwrap(), (2, 2));
+        assert_eq!(parse_ppm_file(&first).unwrap()[0], (2, 2, &buffer[..]));
+        let capacity = buffer.capacity();
+
+        assert_eq!(parse_ppm_into(&second, &mut buffer).unwrap(), (1, 2));
+        assert_eq!(parse_ppm_file(&second).unwrap()[0], (1, 2, &buffer[..]));
+        assert_eq!(buffer.capacity(), capacity);
+
+        let res = parse_ppm_into(b"P6 2 2 255 abc", &mut buffer).unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+    }
+
+    #[test]
+    fn decode_to_rgb() {
+        let mut u8_file = b"P6 2 2 200 ".to_vec();
+        u8_file.extend((0..12).map(|sample| sample * 17));
+        let mut u16_file = b"P6 1 2 1000 ".to_vec();
+        u16_file.extend([0x03, 0xe8, 0, 0, 0x01, 0xf4, 0, 0x64, 0x03, 0xe7, 0, 0x01]);
+
+        for file in [u8_file, u16_file] {
+            let image = &parse_ppm_file(&file).unwrap()[0];
+            let expected: Vec<u8> = image
+                .iter()
+                .flat_map(|pixel| {
+                    let rgba = pixel.rgba();
+                    [rgba.r, rgba.g, rgba.b]
+                })
+                .collect();
+            let (width, height, rgb) = decode_ppm_to_rgb(&file).unwrap();
+            assert_eq!((width, height), (image.width(), image.height()));
+            assert_eq!(rgb, expected);
+        }
+
+        let res = decode_ppm_to_rgb(b"P6 2 2 255 abc").unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn read_images_from_reader() {
+        let mut file = b"P6 1 1 255 abc".to_vec();
+        file.extend(b"\nP6 1 1 255 def");
+        let images = read_ppm_images(&file[..], "<stdin>").unwrap();
+        assert_eq!(images, parse_ppm_file(&file).unwrap());
+
+        let err = read_ppm_images(&b"P6 1 1 0 "[..], "<stdin>").unwrap_err();
+        assert_eq!(err.to_string(), "<stdin>: PPM maxval can't be 0");
+    }
+
+    #[test]
+    fn ppm_format_helpers() {
+        assert_eq!(PpmFormat::from_magic(b"P6"), Some(PpmFormat::P6));
+        assert_eq!(PpmFormat::from_magic(b"P6 "), None);
+        assert_eq!(PpmFormat::from_magic(b"p6"), None);
+
+        assert_eq!(PpmFormat::P4.channels(), Some(Channels::Gray));
+        assert_eq!(PpmFormat::P3.channels(), Some(Channels::Rgb));
+        assert_eq!(PpmFormat::P7.channels(), None);
+        assert!(PpmFormat::P6.is_binary() && PpmFormat::P7.is_binary());
+        assert!(!PpmFormat::P3.is_binary());
+        assert!(PpmFormat::P5.has_maxval() && !PpmFormat::P1.has_maxval());
+
+        let images = parse_ppm_file_with_meta(b"P6 1 1 255 abc").unwrap();
+        assert_eq!(images[0].1.format(), Some(PpmFormat::P6));
+    }
+
+    #[test]
+    fn signs_are_rejected() {
+        let res = parse_ppm_file(b"P6 +4 2 255 ").unwrap_err();
+        match res {
+            ParsingError::WidthIsNotAUsize(ref err)
+                if *err.kind() == IntErrorKind::InvalidDigit => {}
+            _ => panic!("Expected ParsingError::WidthIsNotAUsize found {res}"),
+        };
+
+        let res = parse_ppm_file(b"P6 4 +2 255 ").unwrap_err();
+        match res {
+            ParsingError::HeightIsNotAUsize(_) => {}
+            _ => panic!("Expected ParsingError::HeightIsNotAUsize found {res}"),
+        };
+
+        let res = parse_ppm_file(b"P6 1 1 +255 abc").unwrap_err();
+        match res {
+            ParsingError::MaxvalIsNotAU16(ref err) if *err.kind() == IntErrorKind::InvalidDigit => {
+            }
+            _ => panic!("Expected ParsingError::MaxvalIsNotAU16 found {res}"),
+        };
+
+        let res = parse_ppm_file(b"P6 1 1 -255 abc").unwrap_err();
+        match res {
+            ParsingError::MaxvalIsNotAU16(_) => {}
+            _ => panic!("Expected ParsingError::MaxvalIsNotAU16 found {res}"),
+        };
+    }
+
+    #[test]
+    fn numeric_field_too_long() {
+        let mut file = b"P6 ".to_vec();
+        file.resize(file.len() + 1_000_000, b'0');
+        file.extend(b"1 1 255 abc");
+        let res = parse_ppm_file(&file).unwrap_err();
+        match res {
+            ParsingError::NumericFieldTooLong => {}
+            _ => panic!("Expected ParsingError::NumericFieldTooLong found {res}"),
+        };
+
+        let res = parse_ppm_file(b"P6 1 1 000000000000000000255 abc").unwrap_err();
+        match res {
+            ParsingError::NumericFieldTooLong => {}
+            _ => panic!("Expected ParsingError::NumericFieldTooLong found {res}"),
+        };
+
+        let image = parse_ppm_file(b"P6 00000000000000000001 1 00000000000000000255 abc").unwrap();
+        assert_eq!((image[0].width(), image[0].height()), (1, 1));
+
+        let res = parse_ppm_file(b"P6 1 1 00000000000000000255").unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn streaming_numeric_field_too_long() {
+        let mut file = b"P6 1 ".to_vec();
+        file.extend([b'0'; 1_000]);
+        file.extend(b"1 255 abc");
+        let res = Image::from_ppm_reader_streaming(&file[..]).unwrap_err();
+        match res {
+            ParsingError::NumericFieldTooLong => {}
+            _ => panic!("Expected ParsingError::NumericFieldTooLong found {res}"),
+        };
+    }
+
+    #[test]
+    fn p3_string() {
+        let image = Image::new(
+            2,
+            2,
+            [
+                Rgba::rgba(1, 2, 3, 0).into(),
+                Rgba::WHITE.into(),
+                Rgba::BLACK.into(),
+                Rgba::rgba(10, 200, 30, 4).into(),
+            ],
+        );
+        assert_eq!(
+            image.to_p3_string(),
+            "P3\n2 2\n255\n1 2 3\n255 255 255\n0 0 0\n10 200 30\n"
+        );
+        assert_eq!(Image::new(0, 0, []).to_p3_string(), "P3\n0 0\n255\n");
+    }
+
+    #[test]
+    fn nth_image() {
+        let mut file = b"P6 1 1 255 abc\n".to_vec();
+        file.extend(b"P6 2 1 65535 ");
+        file.extend([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+        file.extend(b" P6 1 2 255 defghi");
+        let images = parse_ppm_file(&file).unwrap();
+
+        for (n, image) in images.iter().enumerate() {
+            assert_eq!(nth_ppm_image(&file, n).unwrap().as_ref(), Some(image));
+        }
+        assert!(nth_ppm_image(&file, 3).unwrap().is_none());
+
+        // The frames after n aren't looked at
+        let mut truncated = file[..file.len() - 3].to_vec();
+        assert_eq!(
+            nth_ppm_image(&truncated, 1).unwrap().as_ref(),
+            Some(&images[1])
+        );
+        truncated.truncate(32);
+        let res = nth_ppm_image(&truncated, 2).unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+    }
+
+    #[test]
+    fn custom_default_alpha() {
+        let mut file = b"P6 2 1 255 abcdef".to_vec();
+        file.extend(b"P6 1 1 65535 abcdef");
+        let options = ParseOptions {
+            default_alpha: 128,
+            ..ParseOptions::unlimited()
+        };
+        let images = parse_ppm_file_with_options(&file, &options).unwrap();
+        assert_eq!(images.len(), 2);
+        assert!(images
+            .iter()
+            .flat_map(|image| image.iter())
+            .all(|pixel| pixel.rgba().a == 128));
+        assert!(parse_ppm_file(&file).unwrap()[0].eq_ignore_alpha(&images[0]));
+
+        let images = parse_ppm_file_with_options(&file, &ParseOptions::default()).unwrap();
+        assert!(images[0].iter().all(|pixel| pixel.rgba().a == 255));
+    }
+
+    #[test]
+    fn every_entry_point_decodes_opaque_pixels() {
+        let file = b"P6 2 1 255 abcdef";
+        let image = &parse_ppm_file(file).unwrap()[0];
+        assert!(image.is_opaque());
+        assert_eq!(ParseOptions::default().default_alpha, DEFAULT_ALPHA_VALUE);
+        assert_eq!(ParseOptions::unlimited().default_alpha, DEFAULT_ALPHA_VALUE);
+
+        let options = parse_ppm_file_with_options(file, &ParseOptions::default()).unwrap();
+        assert_eq!(&options[0], image);
+        let mut buffer = Vec::new();
+        parse_ppm_into(file, &mut buffer).unwrap();
+        assert_eq!(image, &(2, 1, &buffer[..]));
+        assert_eq!(&nth_ppm_image(file, 0).unwrap().unwrap(), image);
+        assert_eq!(&parse_ppm_file_with_meta(file).unwrap()[0].0, image);
+        assert_eq!(&parse_ppm_file_with_capacity(file, 1).unwrap()[0], image);
+        assert_eq!(&Image::try_from(&file[..]).unwrap(), image);
+    }
+
+    #[test]
+    fn try_from_bytes() {
+        let mut file = b"P6 1 1 255 abc".to_vec();
+        file.extend(b" P6 1 1 255 def");
+        let images = parse_ppm_file(&file).unwrap();
+        assert_eq!(Image::try_from(&file[..]).unwrap(), images[0]);
+
+        let res = Image::try_from(&b"P6 1 1 255 abc P6 1 1 0 "[..]).unwrap_err();
+        match res {
+            ParsingError::MaxvalCantBe0 => {}
+            _ => panic!("Expected ParsingError::MaxvalCantBe0 found {res}"),
+        };
+
+        let res = Image::try_from(&b""[..]).unwrap_err();
+        match res {
+            ParsingError::FormatNotFound => {}
+            _ => panic!("Expected ParsingError::FormatNotFound found {res}"),
+        };
+    }
+
+    #[test]
+    fn image_too_large_for_platform() {
+        let bytes_per_pixel = size_of::<Pixel>();
+        for width in [isize::MAX as usize / bytes_per_pixel + 1, usize::MAX / 3] {
+            let res = parse_ppm_file(format!("P6 {width} 1 255 abc").as_bytes()).unwrap_err();
+            match res {
+                ParsingError::ImageTooLargeForPlatform => {}
+                _ => panic!("Expected ParsingError::ImageTooLargeForPlatform found {res}"),
+            };
+        }
+
+        let width = isize::MAX as usize / bytes_per_pixel;
+        let res = parse_ppm_file(format!("P6 {width} 1 255 abc").as_bytes()).unwrap_err();
+        match res {
+            ParsingError::LessThanSizePixelsFoundInFile => {}
+            _ => panic!("Expected ParsingError::LessThanSizePixelsFoundInFile found {res}"),
+        };
+    }
+
     fn push_pixel_data(file: &mut Vec<u8>, pixels: &[Pixel]) {
         for pixel in pixels {
             file.push(pixel.rgba().r);
@@ -0,0 +1,57 @@
+use alloc::vec::Vec;
+use core::arch::x86_64::{
+    __m128i, _mm_loadu_si128, _mm_or_si128, _mm_set1_epi32, _mm_setr_epi8, _mm_shuffle_epi8,
+    _mm_storeu_si128,
+};
+
+use crate::image::Pixel;
+
+const SIZE_OF_RGB_BLOCK: usize = 12;
+const PIXELS_PER_BLOCK: usize = 4;
+
+// Widens as many leading RGB triples of `rgb` as possible into `image_data`,
+// 4 pixels at a time, and returns how many bytes of `rgb` were consumed. Each
+// load reads 16 bytes for 12 useful ones, so the last few pixels are left to
+// the scalar path.
+//
+// SAFETY: the caller must ensure the CPU supports SSSE3, and `image_data` must
+// have enough spare capacity for `rgb.len() / 3` pixels. In the parser, that
+// capacity comes from the try_reserve_exact(size) of read_pixels(), whose
+// `rgb` is the `size * 3` bytes of the raster.
+#[target_feature(enable = "ssse3")]
+pub(super) unsafe fn widen_rgb_to_rgba(
+    rgb: &[u8],
+    image_data: &mut Vec<Pixel>,
+    alpha: u8,
+) -> usize {
+    let shuffle = _mm_setr_epi8(0, 1, 2, -1, 3, 4, 5, -1, 6, 7, 8, -1, 9, 10, 11, -1);
+    let alpha = _mm_set1_epi32(i32::from_ne_bytes([0, 0, 0, alpha]));
+
+    let blocks = rgb.len().saturating_sub(4) / SIZE_OF_RGB_BLOCK;
+    let spare = image_data.spare_capacity_mut();
+    assert!(spare.len() >= blocks * PIXELS_PER_BLOCK);
+
+    for block in 0..blocks {
+        // SAFETY: block * 12 + 16 <= rgb.len() and block * 4 + 4 <= spare.len(),
+        // Pixel is 4 bytes wide so 4 of them are exactly one __m128i
+        unsafe {
+            let input = _mm_loadu_si128(
+                rgb.as_ptr()
+                    .add(block * SIZE_OF_RGB_BLOCK)
+                    .cast::<__m128i>(),
+            );
+            let output = _mm_or_si128(_mm_shuffle_epi8(input, shuffle), alpha);
+            _mm_storeu_si128(
+                spare
+                    .as_mut_ptr()
+                    .add(block * PIXELS_PER_BLOCK)
+                    .cast::<__m128i>(),
+                output,
+            );
+        }
+    }
+
+    // SAFETY: the first blocks * 4 spare pixels were initialized above
+    unsafe { image_data.set_len(image_data.len() + blocks * PIXELS_PER_BLOCK) };
+    blocks * SIZE_OF_RGB_BLOCK
+}
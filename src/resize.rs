@@ -0,0 +1,311 @@
+use alloc::vec::Vec;
+
+use crate::{
+    image::{Pixel, Rgba},
+    Image, ImageNewError,
+};
+
+impl Image {
+    /// Box filter resize, each destination pixel is the average of the source
+    /// pixels it covers.
+    ///
+    /// When `premultiplied_resize` is set the colors are weighted by their alpha
+    /// while averaging, so fully transparent pixels don't bleed their color into
+    /// opaque neighbours.
+    ///
+    /// # Panics
+    ///
+    /// When `new_width * new_height` overflows usize, the panic message is
+    /// `Image::resize() ` followed by the error.
+    pub fn resize(&self, new_width: usize, new_height: usize, premultiplied_resize: bool) -> Image {
+        let size = new_size(new_width, new_height, "Image::resize()");
+        let mut data = Vec::with_capacity(size);
+        if self.width() == 0 || self.height() == 0 {
+            data.resize(size, Pixel::from(0));
+            return Image::new(new_width, new_height, data);
+        }
+
+        for y in 0..new_height {
+            let (y_start, y_end) = source_range(y, new_height, self.height());
+            for x in 0..new_width {
+                let (x_start, x_end) = source_range(x, new_width, self.width());
+
+                let mut sum = [0u64; 4];
+                for source_y in y_start..y_end {
+                    for source_x in x_start..x_end {
+                        let rgba = self[source_y * self.width() + source_x].rgba();
+                        let weight = if premultiplied_resize {
+                            rgba.a as u64
+                        } else {
+                            1
+                        };
+                        sum[0] += rgba.r as u64 * weight;
+                        sum[1] += rgba.g as u64 * weight;
+                        sum[2] += rgba.b as u64 * weight;
+                        sum[3] += rgba.a as u64;
+                    }
+                }
+
+                let count = ((x_end - x_start) * (y_end - y_start)) as u64;
+                let color_weight = if premultiplied_resize { sum[3] } else { count };
+                let average =
+                    |sum: u64, count: u64| (sum + count / 2).checked_div(count).unwrap_or(0) as u8;
+                data.push(Pixel::from(Rgba {
+                    r: average(sum[0], color_weight),
+                    g: average(sum[1], color_weight),
+                    b: average(sum[2], color_weight),
+                    a: average(sum[3], count),
+                }));
+            }
+        }
+
+        Image::new(new_width, new_height, data)
+    }
+
+    /// Each destination pixel is interpolated from the four source pixels
+    /// around its center, the source coordinates are clamped to the borders.
+    ///
+    /// When `premultiplied_resize` is set the colors are weighted by their alpha
+    /// while interpolating, like [`Image::resize`] does.
+    ///
+    /// # Panics
+    ///
+    /// When `new_width * new_height` overflows usize, the panic message is
+    /// `Image::resize_bilinear() ` followed by the error.
+    pub fn resize_bilinear(
+        &self,
+        new_width: usize,
+        new_height: usize,
+        premultiplied_resize: bool,
+    ) -> Image {
+        let size = new_size(new_width, new_height, "Image::resize_bilinear()");
+        let mut data = Vec::with_capacity(size);
+        if self.width() == 0 || self.height() == 0 {
+            data.resize(size, Pixel::from(0));
+            return Image::new(new_width, new_height, data);
+        }
+
+        for y in 0..new_height {
+            let (y0, y1, y_weight) = bilinear_source(y, new_height, self.height());
+            for x in 0..new_width {
+                let (x0, x1, x_weight) = bilinear_source(x, new_width, self.width());
+
+                let top_left = self[y0 * self.width() + x0].rgba();
+                let top_right = self[y0 * self.width() + x1].rgba();
+                let bottom_left = self[y1 * self.width() + x0].rgba();
+                let bottom_right = self[y1 * self.width() + x1].rgba();
+                let interpolate = |channel: &dyn Fn(Rgba) -> f32| {
+                    let top = channel(top_left) * (1. - x_weight) + channel(top_right) * x_weight;
+                    let bottom =
+                        channel(bottom_left) * (1. - x_weight) + channel(bottom_right) * x_weight;
+                    top * (1. - y_weight) + bottom * y_weight
+                };
+                let alpha = interpolate(&|rgba| rgba.a as f32);
+                let color = |channel: fn(Rgba) -> u8| {
+                    let value = if premultiplied_resize {
+                        let sum = interpolate(&|rgba| channel(rgba) as f32 * rgba.a as f32);
+                        if alpha == 0. {
+                            0.
+                        } else {
+                            sum / alpha
+                        }
+                    } else {
+                        interpolate(&|rgba| channel(rgba) as f32)
+                    };
+                    (value + 0.5).min(255.) as u8
+                };
+                data.push(Pixel::from(Rgba {
+                    r: color(|rgba| rgba.r),
+                    g: color(|rgba| rgba.g),
+                    b: color(|rgba| rgba.b),
+                    a: (alpha + 0.5) as u8,
+                }));
+            }
+        }
+
+        Image::new(new_width, new_height, data)
+    }
+}
+
+fn new_size(new_width: usize, new_height: usize, function: &str) -> usize {
+    new_width
+        .checked_mul(new_height)
+        .unwrap_or_else(|| panic!("{function} {}", ImageNewError::DimensionOverflow))
+}
+
+/// Returns the two source indexes around the center of the destination index
+/// and the weight of the second one
+fn bilinear_source(index: usize, new_len: usize, len: usize) -> (usize, usize, f32) {
+    let center = (index as f32 + 0.5) * len as f32 / new_len as f32 - 0.5;
+    let center = center.clamp(0., (len - 1) as f32);
+    // center is positive so the cast truncates like floor()
+    let first = center as usize;
+    let second = (first + 1).min(len - 1);
+    (first, second, center - first as f32)
+}
+
+fn source_range(index: usize, new_len: usize, len: usize) -> (usize, usize) {
+    let start = index * len / new_len;
+    let end = ((index + 1) * len).div_ceil(new_len);
+    (start, end.max(start + 1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::image::rgba;
+
+    #[test]
+    fn premultiplied_downscale() {
+        let image = Image::new(2, 1, [rgba(255, 0, 0, 255), rgba(0, 0, 0, 0)]);
+
+        let res = image.resize(1, 1, true);
+        assert_eq!(
+            res[0].rgba(),
+            Rgba {
+                r: 255,
+                g: 0,
+                b: 0,
+                a: 128
+            }
+        );
+
+        let res = image.resize(1, 1, false);
+        assert_eq!(
+            res[0].rgba(),
+            Rgba {
+                r: 128,
+                g: 0,
+                b: 0,
+                a: 128
+            }
+        );
+    }
+
+    #[test]
+    fn box_resize() {
+        let image = Image::new(
+            4,
+            2,
+            [
+                rgba(0, 0, 0, 255),
+                rgba(100, 0, 0, 255),
+                rgba(10, 10, 10, 255),
+                rgba(10, 10, 10, 255),
+                rgba(0, 0, 0, 255),
+                rgba(100, 0, 0, 255),
+                rgba(10, 10, 10, 255),
+                rgba(10, 10, 10, 255),
+            ],
+        );
+        let res = image.resize(2, 1, false);
+        let expected = Image::new(2, 1, [rgba(50, 0, 0, 255), rgba(10, 10, 10, 255)]);
+        assert_eq!(expected, res);
+
+        let res = res.resize(4, 2, true);
+        assert_eq!(res.width(), 4);
+        assert_eq!(res.height(), 2);
+        assert_eq!(
+            res[5].rgba(),
+            Rgba {
+                r: 50,
+                g: 0,
+                b: 0,
+                a: 255
+            }
+        );
+        assert_eq!(
+            res[7].rgba(),
+            Rgba {
+                r: 10,
+                g: 10,
+                b: 10,
+                a: 255
+            }
+        );
+    }
+
+    #[test]
+    fn bilinear_upscale() {
+        let image = Image::new(
+            2,
+            2,
+            [
+                rgba(0, 0, 0, 255),
+                rgba(255, 0, 0, 255),
+                rgba(0, 255, 0, 255),
+                rgba(255, 255, 0, 255),
+            ],
+        );
+
+        let res = image.resize_bilinear(4, 4, false);
+        assert_eq!((res.width(), res.height()), (4, 4));
+        let reds: Vec<u8> = (0..4).map(|x| res[x].rgba().r).collect();
+        assert_eq!(reds, [0, 64, 191, 255]);
+        let greens: Vec<u8> = (0..4).map(|y| res[y * 4].rgba().g).collect();
+        assert_eq!(greens, [0, 64, 191, 255]);
+        assert_eq!(
+            res[5].rgba(),
+            Rgba {
+                r: 64,
+                g: 64,
+                b: 0,
+                a: 255
+            }
+        );
+        assert!(res.iter().all(|pixel| pixel.rgba().a == 255));
+    }
+
+    #[test]
+    fn bilinear_keeps_solid_color() {
+        let image = Image::new(3, 2, [rgba(10, 20, 30, 40); 6]);
+        for premultiplied_resize in [false, true] {
+            let res = image.resize_bilinear(5, 7, premultiplied_resize);
+            assert!(res.iter().all(|pixel| pixel.rgba()
+                == Rgba {
+                    r: 10,
+                    g: 20,
+                    b: 30,
+                    a: 40
+                }));
+        }
+    }
+
+    #[test]
+    fn premultiplied_bilinear() {
+        let image = Image::new(2, 1, [rgba(255, 0, 0, 255), rgba(0, 0, 0, 0)]);
+
+        let res = image.resize_bilinear(3, 1, true);
+        assert_eq!(
+            res,
+            Image::new(
+                3,
+                1,
+                [rgba(255, 0, 0, 255), rgba(255, 0, 0, 128), rgba(0, 0, 0, 0)]
+            )
+        );
+
+        let res = image.resize_bilinear(3, 1, false);
+        assert_eq!(
+            res[1].rgba(),
+            Rgba {
+                r: 128,
+                g: 0,
+                b: 0,
+                a: 128
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Image::resize() width * height overflowed")]
+    fn resize_overflow_panics() {
+        Image::new(1, 1, [rgba(0, 0, 0, 0)]).resize(usize::MAX, 2, false);
+    }
+
+    #[test]
+    #[should_panic(expected = "Image::resize_bilinear() width * height overflowed")]
+    fn resize_bilinear_overflow_panics() {
+        Image::new(1, 1, [rgba(0, 0, 0, 0)]).resize_bilinear(2, usize::MAX, false);
+    }
+}
@@ -0,0 +1,361 @@
+use alloc::vec::Vec;
+use core::ops::{Deref, Index};
+
+use crate::{Image, ImageNewError, Pixel};
+
+/// Borrowed pixels with their dimensions, the read only counterpart of Image
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageView<'a> {
+    data: &'a [Pixel],
+
+    width: usize,
+    height: usize,
+}
+
+impl<'a> ImageView<'a> {
+    pub fn new(width: usize, height: usize, data: &'a [Pixel]) -> Result<Self, ImageNewError> {
+        let expected = width
+            .checked_mul(height)
+            .ok_or(ImageNewError::DimensionOverflow)?;
+        if expected != data.len() {
+            return Err(ImageNewError::DataLengthMismatch {
+                expected,
+                actual: data.len(),
+            });
+        }
+
+        Ok(Self {
+            data,
+            width,
+            height,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &'a [Pixel] {
+        self.data
+    }
+
+    pub fn to_image(&self) -> Image {
+        Image::new(self.width, self.height, self.data)
+    }
+}
+
+impl Deref for ImageView<'_> {
+    type Target = [Pixel];
+
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl Image {
+    pub fn view(&self) -> ImageView<'_> {
+        ImageView {
+            data: self,
+            width: self.width(),
+            height: self.height(),
+        }
+    }
+}
+
+/// Borrowed rectangle of an image, its rows aren't contiguous so it can't be
+/// an ImageView. Indexed by `(x, y)` relative to its top left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageWindow<'a> {
+    data: &'a [Pixel],
+    stride: usize,
+
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<'a> ImageWindow<'a> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&'a Pixel> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(&self.data[(self.y + y) * self.stride + self.x + x])
+    }
+
+    /// The rows of pixels from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &'a [Pixel]> {
+        let window = *self;
+        (window.y..window.y + window.height).map(move |y| {
+            let start = y * window.stride + window.x;
+            &window.data[start..start + window.width]
+        })
+    }
+
+    /// The pixels with their `(x, y)` relative to the top left corner
+    pub fn enumerate_pixels(&self) -> impl Iterator<Item = (usize, usize, &'a Pixel)> {
+        self.rows()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, pixel)| (x, y, pixel)))
+    }
+
+    /// Same as [`ImageRead::histogram`] for the pixels of the window
+    pub fn histogram(&self) -> [[u32; 256]; 4] {
+        histogram(self.rows().flatten())
+    }
+
+    /// Same as [`ImageRead::luminance_histogram`] for the pixels of the window
+    pub fn luminance_histogram(&self) -> [u32; 256] {
+        luminance_histogram(self.rows().flatten())
+    }
+
+    pub fn to_image(&self) -> Image {
+        let data: Vec<Pixel> = self.rows().flatten().copied().collect();
+        Image::new(self.width, self.height, data)
+    }
+}
+
+impl Index<(usize, usize)> for ImageWindow<'_> {
+    type Output = Pixel;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        self.get(x, y).unwrap_or_else(|| {
+            panic!(
+                "({x}, {y}) is out of the {}x{} window",
+                self.width, self.height
+            )
+        })
+    }
+}
+
+impl Image {
+    /// The `width` by `height` rectangle whose top left corner is at `(x, y)`,
+    /// without copying it. None when it doesn't fit in the image.
+    pub fn window(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<ImageWindow<'_>> {
+        let fits = |start: usize, len: usize, max: usize| {
+            start.checked_add(len).is_some_and(|end| end <= max)
+        };
+        if !fits(x, width, self.width()) || !fits(y, height, self.height()) {
+            return None;
+        }
+        Some(ImageWindow {
+            data: self,
+            stride: self.width(),
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+}
+
+/// The read only operations shared by Image and ImageView, so that they can be
+/// written once for both
+pub trait ImageRead {
+    fn view(&self) -> ImageView<'_>;
+
+    fn enumerate_pixels(&self) -> impl Iterator<Item = (usize, usize, &Pixel)> {
+        let view = self.view();
+        let width = view.width();
+        view.pixels()
+            .iter()
+            .enumerate()
+            .map(move |(i, pixel)| (i % width, i / width, pixel))
+    }
+
+    /// The rows of pixels from top to bottom
+    fn rows(&self) -> impl Iterator<Item = &[Pixel]> {
+        let view = self.view();
+        let width = view.width();
+        (0..view.height()).map(move |y| &view.pixels()[y * width..(y + 1) * width])
+    }
+
+    /// The columns of pixels from left to right, each from top to bottom
+    fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &Pixel>> {
+        let view = self.view();
+        let width = view.width();
+        (0..width).map(move |x| view.pixels().iter().skip(x).step_by(width))
+    }
+
+    /// Counts of each value of the red, green, blue and alpha channels, in
+    /// this order
+    fn histogram(&self) -> [[u32; 256]; 4] {
+        histogram(self.view().pixels())
+    }
+
+    /// Counts of each value of the luminance, see Rgba::luminance()
+    fn luminance_histogram(&self) -> [u32; 256] {
+        luminance_histogram(self.view().pixels())
+    }
+}
+
+fn histogram<'a>(pixels: impl IntoIterator<Item = &'a Pixel>) -> [[u32; 256]; 4] {
+    let mut histogram = [[0; 256]; 4];
+    for pixel in pixels {
+        let rgba = pixel.rgba();
+        histogram[0][rgba.r as usize] += 1;
+        histogram[1][rgba.g as usize] += 1;
+        histogram[2][rgba.b as usize] += 1;
+        histogram[3][rgba.a as usize] += 1;
+    }
+    histogram
+}
+
+fn luminance_histogram<'a>(pixels: impl IntoIterator<Item = &'a Pixel>) -> [u32; 256] {
+    let mut histogram = [0; 256];
+    for pixel in pixels {
+        histogram[pixel.rgba().luminance() as usize] += 1;
+    }
+    histogram
+}
+
+impl ImageRead for Image {
+    fn view(&self) -> ImageView<'_> {
+        Image::view(self)
+    }
+}
+
+impl ImageRead for ImageView<'_> {
+    fn view(&self) -> ImageView<'_> {
+        *self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn sum_of_rows(image: &impl ImageRead) -> Vec<u32> {
+        image
+            .rows()
+            .map(|row| row.iter().map(|pixel| pixel.color()).sum())
+            .collect()
+    }
+
+    #[test]
+    fn view_shares_the_image_operations() {
+        let data: Vec<Pixel> = (0..6).map(Pixel::from).collect();
+        let image = Image::new(3, 2, data.clone());
+        let view = ImageView::new(3, 2, &data).unwrap();
+
+        assert_eq!(image.view(), view);
+        assert_eq!(sum_of_rows(&image), [3, 12]);
+        assert_eq!(sum_of_rows(&view), [3, 12]);
+        assert_eq!(ImageRead::histogram(&image), view.histogram());
+        assert!(view.enumerate_pixels().eq(image.enumerate_pixels()));
+        assert_eq!(
+            view.enumerate_pixels().nth(4),
+            Some((1, 1, &Pixel::from(4)))
+        );
+        assert_eq!(view.to_image(), image);
+    }
+
+    #[test]
+    fn columns() {
+        let image = Image::new(3, 2, (0..6).map(Pixel::from).collect::<Vec<_>>());
+        let columns: Vec<Vec<u32>> = image
+            .columns()
+            .map(|column| column.map(|pixel| pixel.color()).collect())
+            .collect();
+        assert_eq!(columns, [[0, 3], [1, 4], [2, 5]]);
+        assert!(image
+            .view()
+            .columns()
+            .flatten()
+            .eq(image.columns().flatten()));
+
+        assert_eq!(Image::new(0, 3, []).columns().count(), 0);
+        assert!(Image::new(2, 0, [])
+            .columns()
+            .all(|mut column| column.next().is_none()));
+    }
+
+    #[test]
+    fn view_errors() {
+        let data = [Pixel::from(0); 4];
+        assert_eq!(
+            ImageView::new(3, 2, &data),
+            Err(ImageNewError::DataLengthMismatch {
+                expected: 6,
+                actual: 4
+            })
+        );
+        assert_eq!(
+            ImageView::new(usize::MAX, 2, &data),
+            Err(ImageNewError::DimensionOverflow)
+        );
+
+        let empty = ImageView::new(0, 3, &[]).unwrap();
+        assert_eq!(empty.rows().count(), 3);
+        assert!(empty.rows().all(|row| row.is_empty()));
+    }
+
+    #[test]
+    fn window() {
+        let image = Image::new(4, 3, (0..12).map(Pixel::from).collect::<Vec<_>>());
+        let window = image.window(1, 1, 2, 2).unwrap();
+        assert_eq!((window.width(), window.height()), (2, 2));
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            assert_eq!(window[(x, y)], image[(y + 1) * 4 + x + 1]);
+        }
+        assert_eq!(window.get(2, 0), None);
+        assert_eq!(window.get(0, 2), None);
+
+        let rows: Vec<Vec<u32>> = window
+            .rows()
+            .map(|row| row.iter().map(|pixel| pixel.color()).collect())
+            .collect();
+        assert_eq!(rows, [[5, 6], [9, 10]]);
+        let expected = [5, 6, 9, 10].map(Pixel::from);
+        assert_eq!(window.to_image(), (2, 2, &expected[..]));
+
+        assert!(image.window(0, 0, 4, 3).is_some());
+        assert!(image.window(4, 3, 0, 0).is_some());
+        assert!(image.window(3, 0, 2, 1).is_none());
+        assert!(image.window(0, 1, 1, 3).is_none());
+        assert!(image.window(usize::MAX, 0, 2, 1).is_none());
+    }
+
+    #[test]
+    fn window_histograms() {
+        let data: Vec<Pixel> = (0..20u32).map(|i| Pixel::from(i * 0x01030507)).collect();
+        let image = Image::new(5, 4, data);
+        let window = image.window(1, 2, 3, 2).unwrap();
+        let copy = window.to_image();
+
+        assert_eq!(window.histogram(), copy.histogram());
+        assert_eq!(window.luminance_histogram(), copy.luminance_histogram());
+        assert_ne!(window.histogram(), image.histogram());
+        assert!(window.enumerate_pixels().eq(copy.enumerate_pixels()));
+        assert_eq!(
+            window.enumerate_pixels().nth(4),
+            Some((1, 1, &image[3 * 5 + 2]))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "(2, 0) is out of the 2x2 window")]
+    fn window_index_out_of_bounds() {
+        let image = Image::new(4, 3, [Pixel::from(0); 12]);
+        let _ = image.window(1, 1, 2, 2).unwrap()[(2, 0)];
+    }
+}